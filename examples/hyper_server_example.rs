@@ -3,18 +3,14 @@ use std::{convert::Infallible, net::SocketAddr};
 use bytes::Bytes;
 use futures_util::StreamExt;
 use http_body_util::{BodyStream, Full};
-use hyper::{body::Incoming, header::CONTENT_TYPE, Request, Response, StatusCode};
+use hyper::{body::Incoming, Request, Response, StatusCode};
 // Import the multer types.
 use multer::Multipart;
 
 // A handler for incoming requests.
 async fn handle(req: Request<Incoming>) -> Result<Response<Full<Bytes>>, Infallible> {
     // Extract the `multipart/form-data` boundary from the headers.
-    let boundary = req
-        .headers()
-        .get(CONTENT_TYPE)
-        .and_then(|ct| ct.to_str().ok())
-        .and_then(|ct| multer::parse_boundary(ct).ok());
+    let boundary = multer::parse_boundary_from_headers(req.headers()).ok();
 
     // Send `BAD_REQUEST` status if the content-type is not multipart/form-data.
     if boundary.is_none() {
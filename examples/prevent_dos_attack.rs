@@ -39,7 +39,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("Name: {:?}, File Name: {:?}", name, file_name);
 
         // Read field content as text.
-        let content = field.text().await?;
+        let content = field.read_to_string().await?;
         println!("Content: {:?}", content);
     }
 
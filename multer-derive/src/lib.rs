@@ -0,0 +1,150 @@
+//! Derive macro for [`multer`](https://docs.rs/multer).
+//!
+//! This crate is not meant to be used directly, enable the `derive` feature of
+//! `multer` and use `multer::FromMultipart` instead.
+
+#![forbid(unsafe_code)]
+#![warn(rust_2018_idioms, unused_qualifications)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, GenericArgument, PathArguments, Type};
+
+/// Derives `multer::FromMultipart` for a struct with named fields.
+///
+/// Each struct member is filled from the multipart field of the same name,
+/// regardless of the order in which the fields appear in the stream. Member
+/// types must implement `multer::FromField`, and can be wrapped in `Option<T>`
+/// for optional fields or `Vec<T>` for repeated fields, except `Vec<u8>` which
+/// holds the data of a single field. Fields with unknown names are ignored.
+#[proc_macro_derive(FromMultipart)]
+pub fn derive_from_multipart(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+enum Kind {
+    Required,
+    Optional,
+    Repeated,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "`FromMultipart` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`FromMultipart` can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut decls = Vec::new();
+    let mut arms = Vec::new();
+    let mut inits = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let name = ident.to_string();
+        let name = name.strip_prefix("r#").unwrap_or(&name);
+
+        let (kind, ty) = match wrapped_type(&field.ty, "Option") {
+            Some(ty) => (Kind::Optional, ty),
+            None => match wrapped_type(&field.ty, "Vec") {
+                // `Vec<u8>` holds the data of a single field, like `Bytes`.
+                Some(ty) if is_u8(ty) => (Kind::Required, &field.ty),
+                Some(ty) => (Kind::Repeated, ty),
+                None => (Kind::Required, &field.ty),
+            },
+        };
+
+        let convert = quote! { <#ty as ::multer::FromField>::from_field(field).await? };
+        match kind {
+            Kind::Repeated => {
+                decls.push(quote! { let mut #ident: ::std::vec::Vec<#ty> = ::std::vec::Vec::new(); });
+                arms.push(quote! { ::std::option::Option::Some(#name) => #ident.push(#convert), });
+                inits.push(quote! { #ident });
+            }
+            Kind::Optional => {
+                decls.push(quote! { let mut #ident: ::std::option::Option<#ty> = ::std::option::Option::None; });
+                arms.push(
+                    quote! { ::std::option::Option::Some(#name) => #ident = ::std::option::Option::Some(#convert), },
+                );
+                inits.push(quote! { #ident });
+            }
+            Kind::Required => {
+                decls.push(quote! { let mut #ident: ::std::option::Option<#ty> = ::std::option::Option::None; });
+                arms.push(
+                    quote! { ::std::option::Option::Some(#name) => #ident = ::std::option::Option::Some(#convert), },
+                );
+                inits.push(quote! {
+                    #ident: #ident.ok_or_else(|| ::multer::Error::MissingField {
+                        field_name: ::std::string::String::from(#name),
+                    })?
+                });
+            }
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::multer::FromMultipart for #ident #ty_generics #where_clause {
+            fn from_multipart<'r>(
+                mut multipart: ::multer::Multipart<'r>,
+            ) -> ::std::pin::Pin<
+                ::std::boxed::Box<dyn ::std::future::Future<Output = ::multer::Result<Self>> + ::std::marker::Send + 'r>,
+            > {
+                ::std::boxed::Box::pin(async move {
+                    #(#decls)*
+
+                    while let ::std::option::Option::Some(field) = multipart.next_field().await? {
+                        match field.name() {
+                            #(#arms)*
+                            _ => {}
+                        }
+                    }
+
+                    ::std::result::Result::Ok(#ident { #(#inits),* })
+                })
+            }
+        }
+    })
+}
+
+/// Returns `T` if `ty` is `Wrapper<T>`.
+fn wrapped_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match args.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns whether `ty` is `u8`.
+fn is_u8(ty: &Type) -> bool {
+    matches!(ty, Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident("u8"))
+}
@@ -1,31 +1,37 @@
 use std::fmt;
-use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use bytes::{Buf, Bytes, BytesMut};
-use futures_util::stream::Stream;
 
 use crate::constants;
+use crate::constraints::ChunkCallback;
+use crate::BoxStream;
 
 pub(crate) struct StreamBuffer<'r> {
     pub(crate) eof: bool,
     pub(crate) buf: BytesMut,
-    pub(crate) stream: Pin<Box<dyn Stream<Item = Result<Bytes, crate::Error>> + Send + 'r>>,
+    pub(crate) stream: BoxStream<'r>,
     pub(crate) whole_stream_size_limit: u64,
     pub(crate) stream_size_counter: u64,
+    pub(crate) on_chunk_received: Option<Arc<ChunkCallback>>,
 }
 
 impl<'r> StreamBuffer<'r> {
-    pub fn new<S>(stream: S, whole_stream_size_limit: u64) -> Self
-    where
-        S: Stream<Item = Result<Bytes, crate::Error>> + Send + 'r,
-    {
+    pub fn new(
+        stream: BoxStream<'r>,
+        whole_stream_size_limit: u64,
+        initial_capacity: usize,
+        on_chunk_received: Option<Arc<ChunkCallback>>,
+    ) -> Self {
         StreamBuffer {
             eof: false,
-            buf: BytesMut::new(),
-            stream: Box::pin(stream),
+            // Cap the capacity as it may come from an untrusted `Content-Length`.
+            buf: BytesMut::with_capacity(initial_capacity.min(constants::MAX_PREALLOCATED_BUFFER_SIZE)),
+            stream,
             whole_stream_size_limit,
             stream_size_counter: 0,
+            on_chunk_received,
         }
     }
 
@@ -37,15 +43,12 @@ impl<'r> StreamBuffer<'r> {
         loop {
             match self.stream.as_mut().poll_next(cx) {
                 Poll::Ready(Some(Ok(data))) => {
-                    self.stream_size_counter += data.len() as u64;
+                    self.count_chunk(data.len())?;
+                    self.buf.extend_from_slice(&data);
 
-                    if self.stream_size_counter > self.whole_stream_size_limit {
-                        return Err(crate::Error::StreamSizeExceeded {
-                            limit: self.whole_stream_size_limit,
-                        });
+                    if let Some(on_chunk_received) = &self.on_chunk_received {
+                        on_chunk_received(self.stream_size_counter);
                     }
-
-                    self.buf.extend_from_slice(&data)
                 }
                 Poll::Ready(Some(Err(err))) => return Err(err),
                 Poll::Ready(None) => {
@@ -57,6 +60,41 @@ impl<'r> StreamBuffer<'r> {
         }
     }
 
+    /// Reads the rest of the stream, discarding the data instead of
+    /// buffering it, along with the buffered data.
+    pub fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), crate::Error>> {
+        self.buf.clear();
+
+        while !self.eof {
+            match self.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(data))) => {
+                    self.count_chunk(data.len())?;
+
+                    if let Some(on_chunk_received) = &self.on_chunk_received {
+                        on_chunk_received(self.stream_size_counter);
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Err(err)),
+                Poll::Ready(None) => self.eof = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn count_chunk(&mut self, len: usize) -> Result<(), crate::Error> {
+        self.stream_size_counter += len as u64;
+
+        if self.stream_size_counter > self.whole_stream_size_limit {
+            return Err(crate::Error::StreamSizeExceeded {
+                limit: self.whole_stream_size_limit,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn read_exact(&mut self, size: usize) -> Option<Bytes> {
         if size <= self.buf.len() {
             Some(self.buf.split_to(size).freeze())
@@ -65,16 +103,21 @@ impl<'r> StreamBuffer<'r> {
         }
     }
 
-    pub fn peek_exact(&mut self, size: usize) -> Option<&[u8]> {
+    pub fn peek_exact(&self, size: usize) -> Option<&[u8]> {
         self.buf.get(..size)
     }
 
-    pub fn read_until(&mut self, pattern: &[u8]) -> Option<Bytes> {
-        memchr::memmem::find(&self.buf, pattern).map(|idx| self.buf.split_to(idx + pattern.len()).freeze())
+    pub fn try_peek_until(&self, pattern: &[u8]) -> Option<&[u8]> {
+        memchr::memmem::find(&self.buf, pattern).map(|idx| &self.buf[..idx + pattern.len()])
     }
 
-    pub fn read_to(&mut self, pattern: &[u8]) -> Option<Bytes> {
-        memchr::memmem::find(&self.buf, pattern).map(|idx| self.buf.split_to(idx).freeze())
+    pub fn read_to_with_limit(&mut self, pattern: &[u8], max_bytes: u64) -> crate::Result<Option<Bytes>> {
+        match memchr::memmem::find(&self.buf, pattern) {
+            Some(idx) if idx as u64 <= max_bytes => Ok(Some(self.buf.split_to(idx).freeze())),
+            // The pattern can't start any earlier than this once more data arrives.
+            None if (self.buf.len().saturating_sub(pattern.len().saturating_sub(1)) as u64) <= max_bytes => Ok(None),
+            _ => Err(crate::Error::PreambleSizeExceeded { limit: max_bytes }),
+        }
     }
 
     pub fn advance_past_transport_padding(&mut self) -> bool {
@@ -127,11 +170,7 @@ impl<'r> StreamBuffer<'r> {
             None => {
                 let buf_len = self.buf.len();
                 let rem_boundary_part_max_len = b_len - 1;
-                let rem_boundary_part_idx = if buf_len >= rem_boundary_part_max_len {
-                    buf_len - rem_boundary_part_max_len
-                } else {
-                    0
-                };
+                let rem_boundary_part_idx = buf_len.saturating_sub(rem_boundary_part_max_len);
 
                 trace!("no new field found, not EOF, checking close");
                 let bytes = &self.buf[rem_boundary_part_idx..];
@@ -157,6 +196,31 @@ impl<'r> StreamBuffer<'r> {
         }
     }
 
+    /// Discards the buffered field data up to the next boundary without
+    /// materializing it, returning whether the boundary was found and the
+    /// number of bytes discarded.
+    pub fn advance_to_boundary(&mut self, boundary: &str, field_name: Option<&str>) -> crate::Result<(bool, usize)> {
+        let boundary_deriv = format!("{}{}{}", constants::CRLF, constants::BOUNDARY_EXT, boundary);
+
+        match memchr::memmem::find(&self.buf, boundary_deriv.as_bytes()) {
+            Some(idx) => {
+                trace!("skipped field data, boundary found at {}", idx);
+                // discard \r\n too.
+                self.buf.advance(idx + constants::CRLF.len());
+                Ok((true, idx))
+            }
+            None if self.eof => Err(crate::Error::IncompleteFieldData {
+                field_name: field_name.map(|s| s.to_owned()),
+            }),
+            None => {
+                // Keep the tail which may hold the beginning of the boundary.
+                let skipped = self.buf.len().saturating_sub(boundary_deriv.len() - 1);
+                self.buf.advance(skipped);
+                Ok((false, skipped))
+            }
+        }
+    }
+
     pub fn read_full_buf(&mut self) -> Bytes {
         self.buf.split_to(self.buf.len()).freeze()
     }
@@ -164,6 +228,62 @@ impl<'r> StreamBuffer<'r> {
 
 impl fmt::Debug for StreamBuffer<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("StreamBuffer").finish()
+        f.debug_struct("StreamBuffer")
+            .field("eof", &self.eof)
+            .field("buf_len", &self.buf.len())
+            .field("buf_capacity", &self.buf.capacity())
+            .field("stream_bytes_consumed", &self.stream_size_counter)
+            .field("whole_stream_limit", &self.whole_stream_size_limit)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+
+    fn buffer_with(data: &[u8]) -> StreamBuffer<'static> {
+        let mut buffer = StreamBuffer::new(Box::pin(stream::empty()), u64::MAX, 0, None);
+        buffer.buf.extend_from_slice(data);
+        buffer.eof = true;
+        buffer
+    }
+
+    #[test]
+    fn test_read_field_data_with_special_boundary_chars() {
+        // All the non-alphanumeric characters allowed by RFC 2046.
+        let boundaries = [
+            "a'b", "a(b", "a)b", "a+b", "a_b", "a,b", "a-b", "a.b", "a/b", "a:b", "a=b", "a?b", "a b",
+        ]
+        .iter()
+        .map(|b| b.to_string())
+        .chain(Some("'()+_,-./:=? X".to_owned()));
+
+        for boundary in boundaries {
+            let data = format!("abcd\r\n--{}\r\n", boundary);
+            let mut buffer = buffer_with(data.as_bytes());
+
+            let (done, bytes) = buffer.read_field_data(&boundary, None).unwrap().unwrap();
+            assert!(done, "boundary {:?} not found", boundary);
+            assert_eq!(bytes, "abcd");
+            assert_eq!(buffer.buf, format!("--{}\r\n", boundary).as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_read_field_data_special_boundary_chars_match_literally() {
+        for (boundary, lookalike) in [("a.b", "axb"), ("a?b", "ab"), ("a+b", "aab"), ("(a)", "a")] {
+            let data = format!("abcd\r\n--{}\r\n", lookalike);
+            let mut buffer = buffer_with(data.as_bytes());
+
+            assert!(
+                buffer.read_field_data(boundary, None).is_err(),
+                "{:?} matched {:?}",
+                boundary,
+                lookalike
+            );
+        }
     }
 }
@@ -0,0 +1,267 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+use bytes::Bytes;
+use futures_util::stream::{Stream, StreamExt, TryStreamExt};
+use spin::mutex::spin::SpinMutex as Mutex;
+
+use crate::constraints::Constraints;
+use crate::field::Field;
+use crate::multipart::Multipart;
+use crate::Result;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type Segment<'r> = Pin<Box<dyn Stream<Item = Result<Bytes, BoxError>> + Send + 'r>>;
+
+/// A [`Multipart`] whose source is made of several stream segments appended
+/// over time, e.g. for resumable uploads split across multiple HTTP requests.
+///
+/// Segments are read in the order they are appended. When all the appended
+/// segments have been read, parsing waits for more segments instead of
+/// failing with [`Error::IncompleteStream`](crate::Error::IncompleteStream),
+/// until [`finish()`](Self::finish) is called to mark the end of the data.
+///
+/// To append segments while a [`next_field()`](Self::next_field) call is
+/// waiting for more data, e.g. from the handlers of later requests, use the
+/// [`SegmentAppender`] returned by [`appender()`](Self::appender).
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use bytes::Bytes;
+/// use futures_util::stream::once;
+/// use multer::ChunkedMultipart;
+///
+/// # async fn run() {
+/// let mut multipart = ChunkedMultipart::new("X-BOUNDARY");
+///
+/// // The first request carries the beginning of the body.
+/// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nab";
+/// multipart.append_stream(once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) }));
+///
+/// // The second and last request carries the rest of it.
+/// let data = "cd\r\n--X-BOUNDARY--\r\n";
+/// multipart.append_stream(once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) }));
+/// multipart.finish();
+///
+/// while let Some(field) = multipart.next_field().await.unwrap() {
+///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+/// }
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+/// ```
+#[derive(Debug)]
+pub struct ChunkedMultipart<'r> {
+    multipart: Multipart<'r>,
+    appender: SegmentAppender<'r>,
+}
+
+/// A handle appending stream segments to a [`ChunkedMultipart`], returned by
+/// [`ChunkedMultipart::appender()`].
+///
+/// It can be cloned and moved to other tasks, so that segments can be
+/// appended while the `ChunkedMultipart` is waiting for more data.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use bytes::Bytes;
+/// use futures_util::stream::once;
+/// use multer::ChunkedMultipart;
+///
+/// # async fn run() {
+/// let mut multipart = ChunkedMultipart::new("X-BOUNDARY");
+/// let appender = multipart.appender();
+///
+/// tokio::spawn(async move {
+///     let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+///     appender.append_stream(once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) }));
+///     appender.finish();
+/// });
+///
+/// // Waits for the segment appended by the spawned task.
+/// let field = multipart.next_field().await.unwrap().unwrap();
+/// assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SegmentAppender<'r> {
+    segments: Arc<Mutex<Segments<'r>>>,
+}
+
+struct Segments<'r> {
+    queue: VecDeque<Segment<'r>>,
+    waker: Option<Waker>,
+    finished: bool,
+}
+
+struct SegmentStream<'r> {
+    segments: Arc<Mutex<Segments<'r>>>,
+    current: Option<Segment<'r>>,
+}
+
+impl<'r> ChunkedMultipart<'r> {
+    /// Construct a new `ChunkedMultipart` instance with the given boundary and
+    /// no segments.
+    pub fn new<B>(boundary: B) -> Self
+    where
+        B: Into<String>,
+    {
+        ChunkedMultipart::with_constraints(boundary, Constraints::default())
+    }
+
+    /// Construct a new `ChunkedMultipart` instance with the given boundary,
+    /// constraints, and no segments.
+    ///
+    /// The constraints apply to the data of all the segments together.
+    pub fn with_constraints<B>(boundary: B, constraints: Constraints) -> Self
+    where
+        B: Into<String>,
+    {
+        let segments = Arc::new(Mutex::new(Segments {
+            queue: VecDeque::new(),
+            waker: None,
+            finished: false,
+        }));
+
+        let stream = SegmentStream {
+            segments: segments.clone(),
+            current: None,
+        };
+
+        ChunkedMultipart {
+            multipart: Multipart::with_constraints(stream, boundary, constraints),
+            appender: SegmentAppender { segments },
+        }
+    }
+
+    /// Returns a handle appending segments to this `ChunkedMultipart`, which
+    /// can be used from other tasks while a field is awaited.
+    pub fn appender(&self) -> SegmentAppender<'r> {
+        self.appender.clone()
+    }
+
+    /// Appends a stream segment to be read after all the previously appended
+    /// ones.
+    ///
+    /// See [`SegmentAppender::append_stream()`] for details.
+    pub fn append_stream<S, O, E>(&self, stream: S)
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'r,
+        O: Into<Bytes> + 'static,
+        E: Into<BoxError> + 'r,
+    {
+        self.appender.append_stream(stream);
+    }
+
+    /// Marks the end of the data: once all the appended segments have been
+    /// read, the stream ends.
+    pub fn finish(&self) {
+        self.appender.finish();
+    }
+
+    /// Yields the next [`Field`] if available.
+    ///
+    /// See [`Multipart::next_field()`] for details.
+    pub async fn next_field(&mut self) -> Result<Option<Field<'r>>> {
+        self.multipart.next_field().await
+    }
+
+    /// Yields the next [`Field`] if available.
+    ///
+    /// See [`Multipart::poll_next_field()`] for details.
+    pub fn poll_next_field(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Field<'r>>>> {
+        self.multipart.poll_next_field(cx)
+    }
+
+    /// Yields the next [`Field`] with their positioning index as a tuple
+    /// `(`[`usize`]`, `[`Field`]`)`.
+    ///
+    /// See [`Multipart::next_field_with_idx()`] for details.
+    pub async fn next_field_with_idx(&mut self) -> Result<Option<(usize, Field<'r>)>> {
+        self.multipart.next_field_with_idx().await
+    }
+}
+
+impl<'r> SegmentAppender<'r> {
+    /// Appends a stream segment to be read after all the previously appended
+    /// ones.
+    ///
+    /// This can be called while a [`Field`] is being read, or while the next
+    /// one is awaited, which will then resume with the data of the new
+    /// segment.
+    pub fn append_stream<S, O, E>(&self, stream: S)
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'r,
+        O: Into<Bytes> + 'static,
+        E: Into<BoxError> + 'r,
+    {
+        let stream = stream.map_ok(|b| b.into()).map_err(|err| err.into());
+
+        let mut segments = self.segments.lock();
+        segments.queue.push_back(Box::pin(stream));
+        let waker = segments.waker.take();
+        drop(segments);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Marks the end of the data: once all the appended segments have been
+    /// read, the stream ends.
+    pub fn finish(&self) {
+        let mut segments = self.segments.lock();
+        segments.finished = true;
+        let waker = segments.waker.take();
+        drop(segments);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl Stream for SegmentStream<'_> {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(current) = self.current.as_mut() {
+                match current.poll_next_unpin(cx) {
+                    Poll::Ready(None) => self.current = None,
+                    poll => return poll,
+                }
+            }
+
+            let mut segments = self.segments.lock();
+            match segments.queue.pop_front() {
+                Some(segment) => {
+                    drop(segments);
+                    self.current = Some(segment);
+                }
+                None if segments.finished => return Poll::Ready(None),
+                None => {
+                    segments.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for Segments<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Segments")
+            .field("queued", &self.queue.len())
+            .field("finished", &self.finished)
+            .finish()
+    }
+}
@@ -1,9 +1,23 @@
 use std::borrow::Cow;
 
-pub(crate) const DEFAULT_WHOLE_STREAM_SIZE_LIMIT: u64 = std::u64::MAX;
-pub(crate) const DEFAULT_PER_FIELD_SIZE_LIMIT: u64 = std::u64::MAX;
+pub(crate) const DEFAULT_WHOLE_STREAM_SIZE_LIMIT: u64 = u64::MAX;
+pub(crate) const DEFAULT_PER_FIELD_SIZE_LIMIT: u64 = u64::MAX;
+pub(crate) const DEFAULT_PREAMBLE_SIZE_LIMIT: u64 = u64::MAX;
+pub(crate) const DEFAULT_HEADERS_SIZE_LIMIT: u64 = u64::MAX;
+
+pub(crate) const ENV_WHOLE_STREAM_LIMIT: &str = "MULTER_WHOLE_STREAM_LIMIT";
+pub(crate) const ENV_PER_FIELD_LIMIT: &str = "MULTER_PER_FIELD_LIMIT";
+pub(crate) const ENV_MAX_FIELDS: &str = "MULTER_MAX_FIELDS";
+
+#[cfg(feature = "tokio-io")]
+pub(crate) const SYNC_READER_CHUNK_SIZE: usize = 64 * 1024;
+#[cfg(feature = "tokio-io")]
+pub(crate) const COPY_TO_WRITER_QUEUED_CHUNKS: usize = 8;
+pub(crate) const MAX_PREALLOCATED_BUFFER_SIZE: usize = 1024 * 1024;
 
 pub(crate) const MAX_HEADERS: usize = 32;
+pub(crate) const MAX_BOUNDARY_DETECTION_OFFSET: usize = 1024;
+pub(crate) const MAX_BOUNDARY_LINE_LEN: usize = 256;
 pub(crate) const BOUNDARY_EXT: &str = "--";
 pub(crate) const CR: &str = "\r";
 #[allow(dead_code)]
@@ -15,6 +29,8 @@ pub(crate) const CRLF_CRLF: &str = "\r\n\r\n";
 pub(crate) enum ContentDispositionAttr {
     Name,
     FileName,
+    /// The RFC 5987 `filename*` parameter, e.g. `UTF-8''%E4%BD%A0.txt`.
+    FileNameExt,
 }
 
 fn trim_ascii_ws_start(bytes: &[u8]) -> &[u8] {
@@ -24,6 +40,13 @@ fn trim_ascii_ws_start(bytes: &[u8]) -> &[u8] {
         .map_or_else(|| &bytes[bytes.len()..], |i| &bytes[i..])
 }
 
+fn trim_ascii_ws_end(bytes: &[u8]) -> &[u8] {
+    bytes
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or_else(|| &bytes[..0], |i| &bytes[..=i])
+}
+
 fn trim_ascii_ws_then(bytes: &[u8], char: u8) -> Option<&[u8]> {
     match trim_ascii_ws_start(bytes) {
         [first, rest @ ..] if *first == char => Some(rest),
@@ -37,47 +60,176 @@ impl ContentDispositionAttr {
     /// Some older clients may not quote the name or filename, so we allow them,
     /// but require them to be percent encoded. Only allocates if percent
     /// decoding, and there are characters that need to be decoded.
-    pub fn extract_from<'h>(&self, mut header: &'h [u8]) -> Option<Cow<'h, str>> {
-        // TODO: The prefix should be matched case-insensitively.
+    ///
+    /// The `filename*` value is decoded from its charset, values which can't be
+    /// decoded are ignored.
+    pub fn extract_from<'h>(&self, header: &'h [u8]) -> Option<Cow<'h, str>> {
         let prefix = match self {
             ContentDispositionAttr::Name => &b"name"[..],
             ContentDispositionAttr::FileName => &b"filename"[..],
+            ContentDispositionAttr::FileNameExt => &b"filename*"[..],
         };
 
-        while let Some(i) = memchr::memmem::find(header, prefix) {
-            // Check if we found a superstring of `prefix`; continue if so.
-            let suffix = &header[(i + prefix.len())..];
-            if i > 0 && !(header[i - 1].is_ascii_whitespace() || header[i - 1] == b';') {
+        let value = extract_param(header, prefix)?;
+        match self {
+            ContentDispositionAttr::FileNameExt => decode_ext_value(&value).map(Cow::Owned),
+            _ => Some(value),
+        }
+    }
+
+    /// Extract all the ContentDisposition Attributes from header, in order.
+    pub fn extract_all_from(header: &[u8]) -> Vec<(String, Cow<'_, str>)> {
+        params(header).map(|(name, value)| (name.to_owned(), value)).collect()
+    }
+}
+
+/// Extract the value of the parameter named `prefix` from a
+/// `Content-Disposition` header.
+pub(crate) fn extract_param<'h>(mut header: &'h [u8], prefix: &[u8]) -> Option<Cow<'h, str>> {
+    // TODO: The prefix should be matched case-insensitively.
+    while let Some(i) = memchr::memmem::find(header, prefix) {
+        // Check if we found a superstring of `prefix`; continue if so.
+        let suffix = &header[(i + prefix.len())..];
+        if i > 0 && !(header[i - 1].is_ascii_whitespace() || header[i - 1] == b';') {
+            header = suffix;
+            continue;
+        }
+
+        // Now find and trim the `=`, or keep looking if this isn't a parameter
+        // name. Handle quoted strings first.
+        let rest = match trim_ascii_ws_then(suffix, b'=') {
+            Some(rest) => rest,
+            None => {
                 header = suffix;
                 continue;
             }
+        };
+        let (bytes, is_escaped) = if let Some(rest) = trim_ascii_ws_then(rest, b'"') {
+            let (k, escaped) = find_closing_quote(rest)?;
+            (&rest[..k], escaped)
+        } else {
+            let rest = trim_ascii_ws_start(rest);
+            let j = memchr::memchr2(b';', b' ', rest).unwrap_or(rest.len());
+            (&rest[..j], false)
+        };
+
+        return match std::str::from_utf8(bytes).ok()? {
+            name if is_escaped => Some(unescape_quoted(name).into()),
+            name => Some(name.into()),
+        };
+    }
+
+    None
+}
 
-            // Now find and trim the `=`. Handle quoted strings first.
-            let rest = trim_ascii_ws_then(suffix, b'=')?;
-            let (bytes, is_escaped) = if let Some(rest) = trim_ascii_ws_then(rest, b'"') {
-                let (mut k, mut escaped) = (memchr::memchr(b'"', rest)?, false);
-                while k > 0 && rest[k - 1] == b'\\' {
-                    escaped = true;
-                    k = k + 1 + memchr::memchr(b'"', &rest[(k + 1)..])?;
-                }
-
-                (&rest[..k], escaped)
-            } else {
-                let rest = trim_ascii_ws_start(rest);
-                let j = memchr::memchr2(b';', b' ', rest).unwrap_or(rest.len());
-                (&rest[..j], false)
-            };
-
-            return match std::str::from_utf8(bytes).ok()? {
-                name if is_escaped => Some(name.replace(r#"\""#, "\"").into()),
-                name => Some(name.into()),
-            };
+/// Find the `"` ending a quoted string, i.e. the first one not escaped by an
+/// odd number of backslashes, and whether the string contains backslashes.
+fn find_closing_quote(quoted: &[u8]) -> Option<(usize, bool)> {
+    let mut k = memchr::memchr(b'"', quoted)?;
+    loop {
+        let backslashes = quoted[..k].iter().rev().take_while(|&&b| b == b'\\').count();
+        if backslashes % 2 == 0 {
+            return Some((k, memchr::memchr(b'\\', &quoted[..k]).is_some()));
         }
 
-        None
+        k = k + 1 + memchr::memchr(b'"', &quoted[(k + 1)..])?;
     }
 }
 
+/// Unescape `\"` and `\\` in a quoted string. Other backslashes are kept as
+/// is, as some clients send unescaped Windows paths.
+fn unescape_quoted(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('"' | '\\'))) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            (c, _) => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+/// Decode an RFC 5987 extended parameter value, i.e.
+/// `charset'language'percent-encoded-value`.
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let (charset, _language, encoded) = (parts.next()?, parts.next()?, parts.next()?);
+    let encoding = encoding_rs::Encoding::for_label(charset.trim().as_bytes())?;
+
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let [first, tail @ ..] = rest {
+        match (first, tail) {
+            (b'%', [hi, lo, tail @ ..]) => {
+                let (hi, lo) = ((*hi as char).to_digit(16)?, (*lo as char).to_digit(16)?);
+                bytes.push((hi * 16 + lo) as u8);
+                rest = tail;
+            }
+            (b'%', _) => return None,
+            (byte, tail) => {
+                bytes.push(*byte);
+                rest = tail;
+            }
+        }
+    }
+
+    let (decoded, had_errors) = encoding.decode_without_bom_handling(&bytes);
+    if had_errors {
+        return None;
+    }
+
+    Some(decoded.into_owned())
+}
+
+/// Iterate over all the parameters of a `Content-Disposition` header, skipping
+/// the leading disposition type.
+///
+/// Parameters without a value or which aren't valid UTF-8 are skipped.
+pub(crate) fn params(header: &[u8]) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+    let mut rest = memchr::memchr(b';', header).map_or(&header[header.len()..], |i| &header[(i + 1)..]);
+
+    std::iter::from_fn(move || loop {
+        rest = trim_ascii_ws_start(rest);
+        if rest.is_empty() {
+            return None;
+        }
+
+        let name_end = memchr::memchr2(b'=', b';', rest).unwrap_or(rest.len());
+        let name = trim_ascii_ws_end(&rest[..name_end]);
+        if rest.get(name_end) != Some(&b'=') {
+            rest = rest.get((name_end + 1)..).unwrap_or_default();
+            continue;
+        }
+
+        let value = trim_ascii_ws_start(&rest[(name_end + 1)..]);
+        let (bytes, is_escaped) = if let Some(quoted) = value.strip_prefix(b"\"") {
+            let (k, escaped) = find_closing_quote(quoted)?;
+            let after = &quoted[(k + 1)..];
+            rest = memchr::memchr(b';', after).map_or(&after[after.len()..], |i| &after[(i + 1)..]);
+            (&quoted[..k], escaped)
+        } else {
+            let j = memchr::memchr(b';', value).unwrap_or(value.len());
+            rest = value.get((j + 1)..).unwrap_or_default();
+            (trim_ascii_ws_end(&value[..j]), false)
+        };
+
+        let (name, value) = match (std::str::from_utf8(name), std::str::from_utf8(bytes)) {
+            (Ok(name), Ok(value)) => (name, value),
+            _ => continue,
+        };
+
+        return match value {
+            value if is_escaped => Some((name, unescape_quoted(value).into())),
+            value => Some((name, value.into())),
+        };
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,6 +362,33 @@ mod tests {
         assert_eq!(filename.unwrap(), ";");
     }
 
+    #[test]
+    fn test_content_disposition_filename_ext() {
+        let val = br#"form-data; name="file"; filename*=UTF-8''%E4%BD%A0%E5%A5%BD.txt"#;
+        let filename = ContentDispositionAttr::FileNameExt.extract_from(val);
+        assert_eq!(filename.unwrap(), "你好.txt");
+        assert!(ContentDispositionAttr::FileName.extract_from(val).is_none());
+
+        let val = br#"form-data; filename*=iso-8859-1'en'%A3%20rates.txt"#;
+        let filename = ContentDispositionAttr::FileNameExt.extract_from(val);
+        assert_eq!(filename.unwrap(), "£ rates.txt");
+
+        let val = br#"form-data; filename*="utf-8''a%20b.txt""#;
+        let filename = ContentDispositionAttr::FileNameExt.extract_from(val);
+        assert_eq!(filename.unwrap(), "a b.txt");
+
+        for val in [
+            &br#"form-data; filename*=a.txt"#[..],
+            br#"form-data; filename*=unknown''a.txt"#,
+            br#"form-data; filename*=UTF-8''%E4%BD.txt"#,
+            br#"form-data; filename*=UTF-8''%zz.txt"#,
+            br#"form-data; filename*=UTF-8''%+1.txt"#,
+            br#"form-data; filename*=UTF-8''a%2"#,
+        ] {
+            assert!(ContentDispositionAttr::FileNameExt.extract_from(val).is_none());
+        }
+    }
+
     #[test]
     fn test_content_disposition_name_escaped_quote() {
         let val = br#"form-data; name="my\"field\"name""#;
@@ -219,5 +398,30 @@ mod tests {
         let val = br#"form-data; name="myfield\"name""#;
         let name = ContentDispositionAttr::Name.extract_from(val);
         assert_eq!(name.unwrap(), r#"myfield"name"#);
+
+        let val = br#"form-data; name="my\\"; filename="C:\dir\\\"a.txt""#;
+        let name = ContentDispositionAttr::Name.extract_from(val);
+        let filename = ContentDispositionAttr::FileName.extract_from(val);
+        assert_eq!(name.unwrap(), r#"my\"#);
+        assert_eq!(filename.unwrap(), r#"C:\dir\"a.txt"#);
+    }
+
+    #[test]
+    fn test_content_disposition_params() {
+        let val = br#"form-data; name="my_field"; filename = file.txt ; size=1024;flag; note="a\"b;c""#;
+        let all = params(val).collect::<Vec<_>>();
+        assert_eq!(
+            all,
+            vec![
+                ("name", "my_field".into()),
+                ("filename", "file.txt".into()),
+                ("size", "1024".into()),
+                ("note", r#"a"b;c"#.into()),
+            ]
+        );
+
+        assert_eq!(params(b"form-data").count(), 0);
+        assert_eq!(params(b"form-data; ").count(), 0);
+        assert_eq!(params(br#"form-data; name="unterminated"#).count(), 0);
     }
 }
@@ -1,4 +1,17 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::Error;
+use crate::field::Field;
+use crate::field_constraints::FieldConstraints;
+use crate::size_exceeded_action::SizeExceededAction;
 use crate::size_limit::SizeLimit;
+use crate::unknown_field_action::UnknownFieldAction;
+use crate::validation_failure_action::ValidationFailureAction;
+use crate::version::Version;
+use crate::{constants, helpers};
 
 /// Represents some rules to be applied on the stream and field's content size
 /// to prevent DoS attacks.
@@ -9,6 +22,10 @@ use crate::size_limit::SizeLimit;
 /// make `multipart/form-data` safe. By default, it does not apply any
 /// constraint.
 ///
+/// A base configuration can be cloned to derive per-request variants, e.g.
+/// `base.clone().size_limit(user_limit)`. Callbacks are shared between the
+/// clones.
+///
 /// # Examples
 ///
 /// ```
@@ -39,43 +56,510 @@ use crate::size_limit::SizeLimit;
 /// let mut multipart = Multipart::with_constraints(some_stream, "X-BOUNDARY", constraints);
 ///
 /// while let Some(field) = multipart.next_field().await.unwrap() {
-///     let content = field.text().await.unwrap();
+///     let content = field.read_to_string().await.unwrap();
 ///     assert_eq!(content, "abcd");
 /// }
 /// # }
 /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
 /// ```
-#[derive(Debug, Default)]
+#[derive(Clone, Default)]
 pub struct Constraints {
     pub(crate) size_limit: SizeLimit,
     pub(crate) allowed_fields: Option<Vec<String>>,
+    pub(crate) ignored_fields: Vec<String>,
+    pub(crate) unknown_field_action: UnknownFieldAction,
+    pub(crate) on_unknown_field: Option<Arc<UnknownFieldCallback>>,
+    pub(crate) max_fields_per_name: HashMap<String, usize>,
+    pub(crate) max_fields: Option<usize>,
+    pub(crate) max_field_name_length: Option<usize>,
+    pub(crate) max_filename_length: Option<usize>,
+    pub(crate) field_constraints: HashMap<String, FieldConstraints>,
+    pub(crate) on_chunk_received: Option<Arc<ChunkCallback>>,
+    pub(crate) version: Version,
+    pub(crate) expected_boundary: Option<String>,
+    pub(crate) allow_missing_final_boundary: bool,
+    pub(crate) on_field_size_exceeded: Option<Arc<SizeExceededCallback>>,
+    pub(crate) field_validator: Option<Arc<FieldValidator>>,
+    pub(crate) validation_failure_action: ValidationFailureAction,
 }
 
+pub(crate) type ChunkCallback = dyn Fn(u64) + Send + Sync;
+pub(crate) type SizeExceededCallback = dyn Fn(&str, u64) -> SizeExceededAction + Send + Sync;
+pub(crate) type FieldValidator = dyn Fn(&Field<'_>) -> bool + Send + Sync;
+pub(crate) type UnknownFieldCallback = dyn Fn(&str) -> UnknownFieldAction + Send + Sync;
+
 impl Constraints {
     /// Creates a set of rules with default behaviour.
     pub fn new() -> Constraints {
         Constraints::default()
     }
 
+    /// Creates a set of rules expecting the stream to use the given boundary.
+    ///
+    /// The boundary is validated against the
+    /// [RFC 2046](https://tools.ietf.org/html/rfc2046#section-5.1.1) rules,
+    /// and the [`Multipart`](crate::Multipart) created with these constraints
+    /// fails with [`Error::BoundaryMismatch`] if its boundary is different.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::InvalidBoundary`] if the boundary is
+    /// invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::Constraints;
+    ///
+    /// assert!(Constraints::for_boundary("X-BOUNDARY").is_ok());
+    /// assert!(Constraints::for_boundary("invalid;boundary").is_err());
+    /// ```
+    pub fn for_boundary<B: Into<String>>(boundary: B) -> crate::Result<Constraints> {
+        let boundary = boundary.into();
+
+        if !helpers::is_valid_boundary(&boundary) {
+            return Err(Error::InvalidBoundary { boundary });
+        }
+
+        Ok(Constraints {
+            expected_boundary: Some(boundary),
+            ..Constraints::default()
+        })
+    }
+
+    /// Creates a set of rules with the limits read from the environment
+    /// variables:
+    ///
+    /// * `MULTER_WHOLE_STREAM_LIMIT` for the
+    ///   [`whole_stream`](SizeLimit::whole_stream) limit.
+    /// * `MULTER_PER_FIELD_LIMIT` for the [`per_field`](SizeLimit::per_field)
+    ///   limit.
+    /// * `MULTER_MAX_FIELDS` for the [`max_fields`](Self::max_fields) limit.
+    ///
+    /// Size limits are byte sizes with an optional binary unit suffix, e.g.
+    /// `1024`, `64KB` or `10 MiB`, and `MULTER_MAX_FIELDS` is a plain count.
+    /// Absent variables keep the default limits, invalid ones return an
+    /// [`Error::InvalidEnvVar`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::Constraints;
+    ///
+    /// std::env::set_var("MULTER_PER_FIELD_LIMIT", "10MB");
+    /// std::env::set_var("MULTER_MAX_FIELDS", "100");
+    ///
+    /// let constraints = Constraints::new_from_env().unwrap();
+    /// ```
+    pub fn new_from_env() -> crate::Result<Constraints> {
+        fn read_limit<T>(name: &str, parse: fn(&str) -> Option<T>) -> crate::Result<Option<T>> {
+            match env::var(name) {
+                Ok(value) => parse(&value).map(Some).ok_or_else(|| Error::InvalidEnvVar {
+                    name: name.to_owned(),
+                    value,
+                }),
+                Err(env::VarError::NotPresent) => Ok(None),
+                Err(env::VarError::NotUnicode(value)) => Err(Error::InvalidEnvVar {
+                    name: name.to_owned(),
+                    value: value.to_string_lossy().into_owned(),
+                }),
+            }
+        }
+
+        let mut size_limit = SizeLimit::new();
+
+        if let Some(limit) = read_limit(constants::ENV_WHOLE_STREAM_LIMIT, helpers::parse_byte_size)? {
+            size_limit = size_limit.whole_stream(limit);
+        }
+
+        if let Some(limit) = read_limit(constants::ENV_PER_FIELD_LIMIT, helpers::parse_byte_size)? {
+            size_limit = size_limit.per_field(limit);
+        }
+
+        let mut constraints = Constraints::new().size_limit(size_limit);
+
+        if let Some(max) = read_limit(constants::ENV_MAX_FIELDS, |value| value.trim().parse().ok())? {
+            constraints = constraints.max_fields(max);
+        }
+
+        Ok(constraints)
+    }
+
     /// Applies rules on field's content length.
     pub fn size_limit(self, size_limit: SizeLimit) -> Constraints {
-        Constraints {
-            size_limit,
-            allowed_fields: self.allowed_fields,
-        }
+        Constraints { size_limit, ..self }
     }
 
     /// Specify which fields should be allowed, for any unknown field, the
-    /// [`next_field`](crate::Multipart::next_field) will throw an error.
+    /// [`next_field`](crate::Multipart::next_field) will throw an error,
+    /// unless set otherwise with [`on_unknown_field`](Self::on_unknown_field).
     pub fn allowed_fields<N: Into<String>>(self, allowed_fields: Vec<N>) -> Constraints {
         let allowed_fields = allowed_fields.into_iter().map(|item| item.into()).collect();
 
         Constraints {
-            size_limit: self.size_limit,
             allowed_fields: Some(allowed_fields),
+            ..self
+        }
+    }
+
+    /// Specify which fields should be discarded silently, e.g. hidden
+    /// anti-CSRF tokens, the [`next_field`](crate::Multipart::next_field)
+    /// skips their data and moves on to the next field instead of yielding
+    /// them.
+    ///
+    /// Ignored fields don't need to be in the
+    /// [`allowed_fields`](Self::allowed_fields) and are exempt from the rules
+    /// on their name, but still count towards
+    /// [`max_fields`](Self::max_fields).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::Constraints;
+    ///
+    /// let constraints = Constraints::new()
+    ///     .allowed_fields(vec!["my_text_field"])
+    ///     .ignored_fields(vec!["_csrf"]);
+    /// ```
+    pub fn ignored_fields<N: Into<String>>(self, ignored_fields: Vec<N>) -> Constraints {
+        let ignored_fields = ignored_fields.into_iter().map(|item| item.into()).collect();
+
+        Constraints { ignored_fields, ..self }
+    }
+
+    /// Sets what to do with a field not in the
+    /// [`allowed_fields`](Self::allowed_fields),
+    /// [`UnknownFieldAction::Fail`] by default. It replaces any handler set
+    /// with [`on_unknown_field_with()`](Self::on_unknown_field_with).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::{Constraints, UnknownFieldAction};
+    ///
+    /// // Old clients may still send deprecated fields, ignore them.
+    /// let constraints = Constraints::new()
+    ///     .allowed_fields(vec!["my_text_field"])
+    ///     .on_unknown_field(UnknownFieldAction::Skip);
+    /// ```
+    pub fn on_unknown_field(self, action: UnknownFieldAction) -> Constraints {
+        Constraints {
+            unknown_field_action: action,
+            on_unknown_field: None,
+            ..self
+        }
+    }
+
+    /// Sets a handler deciding what to do with each field not in the
+    /// [`allowed_fields`](Self::allowed_fields), instead of a fixed
+    /// [`on_unknown_field()`](Self::on_unknown_field) action.
+    ///
+    /// The handler is called with the field name, empty for unnamed fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::{Constraints, UnknownFieldAction};
+    ///
+    /// let constraints = Constraints::new()
+    ///     .allowed_fields(vec!["my_text_field"])
+    ///     .on_unknown_field_with(|name| match name.starts_with("legacy_") {
+    ///         true => UnknownFieldAction::Skip,
+    ///         false => UnknownFieldAction::Fail,
+    ///     });
+    /// ```
+    pub fn on_unknown_field_with<F>(self, handler: F) -> Constraints
+    where
+        F: Fn(&str) -> UnknownFieldAction + Send + Sync + 'static,
+    {
+        Constraints {
+            on_unknown_field: Some(Arc::new(handler)),
+            ..self
+        }
+    }
+
+    /// Allow fields with any name, undoing a previous call to
+    /// [`allowed_fields`](Self::allowed_fields).
+    ///
+    /// This is the default behaviour, but makes the intent explicit when the
+    /// constraints are built up in several steps.
+    pub fn any_field(self) -> Constraints {
+        Constraints {
+            allowed_fields: None,
+            ..self
+        }
+    }
+
+    /// Applies rules on the field with the given name, like its size, whether
+    /// a file name is required or which content types are allowed. For any
+    /// field breaking these rules, the
+    /// [`next_field`](crate::Multipart::next_field) will throw an error.
+    ///
+    /// Please refer [`FieldConstraints`] for more info.
+    pub fn for_named_field<N: Into<String>>(mut self, field_name: N, constraints: FieldConstraints) -> Constraints {
+        self.field_constraints.insert(field_name.into(), constraints);
+        self
+    }
+
+    /// Limits the total number of fields in the stream, for any further field
+    /// the [`next_field`](crate::Multipart::next_field) will throw an
+    /// [`Error::FieldCountExceeded`] as soon as its boundary is read.
+    ///
+    /// Unlike [`allowed_fields`](Self::allowed_fields), this also bounds
+    /// repeated fields with allowed names.
+    pub fn max_fields(self, max: usize) -> Constraints {
+        Constraints {
+            max_fields: Some(max),
+            ..self
+        }
+    }
+
+    /// Limits the length in bytes of the field names, for any field with a
+    /// longer name the [`next_field`](crate::Multipart::next_field) will throw
+    /// an [`Error::FieldNameTooLong`] before the field data is read.
+    pub fn max_field_name_length(self, max: usize) -> Constraints {
+        Constraints {
+            max_field_name_length: Some(max),
+            ..self
+        }
+    }
+
+    /// Limits the length in bytes of the file names, for any field with a
+    /// longer file name the [`next_field`](crate::Multipart::next_field) will
+    /// throw an [`Error::FileNameTooLong`] before the field data is read.
+    pub fn max_filename_length(self, max: usize) -> Constraints {
+        Constraints {
+            max_filename_length: Some(max),
+            ..self
+        }
+    }
+
+    /// Allows only the given content types for the field with the given name,
+    /// for any other content type the
+    /// [`next_field`](crate::Multipart::next_field) will throw an
+    /// [`Error::ContentTypeNotAllowed`] before the field data is read.
+    ///
+    /// This is a shortcut for [`FieldConstraints::allowed_types`] which keeps
+    /// the other rules already set for this field with
+    /// [`for_named_field`](Self::for_named_field).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::Constraints;
+    ///
+    /// let constraints =
+    ///     Constraints::new().allowed_content_types_for_field("avatar", vec![mime::IMAGE_JPEG, mime::IMAGE_PNG]);
+    /// ```
+    pub fn allowed_content_types_for_field<N: Into<String>>(
+        mut self,
+        field_name: N,
+        allowed_types: Vec<mime::Mime>,
+    ) -> Constraints {
+        let field_constraints = self.field_constraints.entry(field_name.into()).or_default();
+        field_constraints.allowed_types = Some(allowed_types);
+        self
+    }
+
+    /// Limits how many times a field with the given name may appear in the
+    /// stream, for any further occurrence the
+    /// [`next_field`](crate::Multipart::next_field) will throw an error.
+    ///
+    /// It is useful for fields that accept multiple values, to prevent
+    /// attackers from sending an unbounded number of values for them.
+    pub fn max_fields_per_name<N: Into<String>>(mut self, field_name: N, max: usize) -> Constraints {
+        self.max_fields_per_name.insert(field_name.into(), max);
+        self
+    }
+
+    /// Registers a callback invoked each time a chunk is received from the
+    /// stream, with the total number of bytes received so far.
+    ///
+    /// It is useful to report the upload progress independently of the field
+    /// boundaries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use multer::Constraints;
+    ///
+    /// let constraints = Constraints::new().on_chunk_received(Arc::new(|received| {
+    ///     println!("received {} bytes", received);
+    /// }));
+    /// ```
+    pub fn on_chunk_received(self, f: Arc<dyn Fn(u64) + Send + Sync>) -> Constraints {
+        Constraints {
+            on_chunk_received: Some(f),
+            ..self
+        }
+    }
+
+    /// Sets a handler deciding what to do when a field exceeds its size limit,
+    /// instead of failing with [`Error::FieldSizeExceeded`].
+    ///
+    /// The handler is called with the field name, empty for unnamed fields,
+    /// and its size limit, at most once per field. The rest of a truncated or
+    /// skipped field is discarded and the parsing goes on with the next field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::{Constraints, Multipart, SizeExceededAction, SizeLimit};
+    /// # use bytes::Bytes;
+    /// # use std::convert::Infallible;
+    /// # use futures_util::stream::once;
+    ///
+    /// # async fn run() {
+    /// # let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// # let some_stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let constraints = Constraints::new()
+    ///     .size_limit(SizeLimit::new().per_field(2))
+    ///     .on_field_size_exceeded(|_name, _limit| SizeExceededAction::Truncate);
+    ///
+    /// let mut multipart = Multipart::with_constraints(some_stream, "X-BOUNDARY", constraints);
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "ab");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn on_field_size_exceeded<F>(self, handler: F) -> Constraints
+    where
+        F: Fn(&str, u64) -> SizeExceededAction + Send + Sync + 'static,
+    {
+        Constraints {
+            on_field_size_exceeded: Some(Arc::new(handler)),
+            ..self
+        }
+    }
+
+    /// Sets a predicate called with each [`Field`] once its headers are
+    /// parsed, before it is yielded.
+    ///
+    /// A field for which it returns `false` fails with
+    /// [`Error::FieldValidationFailed`], or is skipped depending on
+    /// [`on_validation_failure()`](Self::on_validation_failure). This allows
+    /// any check on the field name, file name, content type or headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::{Constraints, Multipart, ValidationFailureAction};
+    /// # use bytes::Bytes;
+    /// # use std::convert::Infallible;
+    /// # use futures_util::stream::once;
+    ///
+    /// # async fn run() {
+    /// # let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// # let some_stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// // Skip the fields having a name starting with `_`.
+    /// let constraints = Constraints::new()
+    ///     .with_field_validator(|field| !field.name().unwrap_or_default().starts_with('_'))
+    ///     .on_validation_failure(ValidationFailureAction::Skip);
+    ///
+    /// let mut multipart = Multipart::with_constraints(some_stream, "X-BOUNDARY", constraints);
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn with_field_validator<F>(self, validator: F) -> Constraints
+    where
+        F: Fn(&Field<'_>) -> bool + Send + Sync + 'static,
+    {
+        Constraints {
+            field_validator: Some(Arc::new(validator)),
+            ..self
+        }
+    }
+
+    /// Sets what to do with a field rejected by the
+    /// [`field validator`](Self::with_field_validator),
+    /// [`ValidationFailureAction::Fail`] by default.
+    pub fn on_validation_failure(self, action: ValidationFailureAction) -> Constraints {
+        Constraints {
+            validation_failure_action: action,
+            ..self
+        }
+    }
+
+    /// Whether to accept a stream which ends in the middle of a field's data,
+    /// e.g. an upload interrupted by the client, `false` by default.
+    ///
+    /// When allowed, the last field yields the data received so far and
+    /// [`Multipart::next_field()`](crate::Multipart::next_field) then returns
+    /// `None` instead of failing with [`Error::IncompleteFieldData`]. The
+    /// missing final boundary is only logged as a warning with the `log`
+    /// feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::{Constraints, Multipart};
+    /// # use bytes::Bytes;
+    /// # use std::convert::Infallible;
+    /// # use futures_util::stream::once;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"\r\n\r\nabcd";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    ///
+    /// let constraints = Constraints::new().allow_missing_final_boundary(true);
+    /// let mut multipart = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
+    ///
+    /// let field = multipart.next_field().await.unwrap().unwrap();
+    /// assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// assert!(multipart.next_field().await.unwrap().is_none());
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn allow_missing_final_boundary(self, allow: bool) -> Constraints {
+        Constraints {
+            allow_missing_final_boundary: allow,
+            ..self
         }
     }
 
+    /// Sets the version of the parsing behaviour, [`Version::V1`] by default.
+    ///
+    /// Only [`Version::V1`] exists for now.
+    pub fn version(self, version: Version) -> Constraints {
+        Constraints { version, ..self }
+    }
+
+    /// Whether no rule is applied, i.e. the size limits are the default ones
+    /// and any field is accepted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::Constraints;
+    ///
+    /// assert!(Constraints::new().is_empty());
+    /// assert!(!Constraints::new().allowed_fields(vec!["name"]).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.size_limit.is_default()
+            && self.allowed_fields.is_none()
+            && self.ignored_fields.is_empty()
+            && self.unknown_field_action == UnknownFieldAction::Fail
+            && self.on_unknown_field.is_none()
+            && self.max_fields_per_name.is_empty()
+            && self.max_fields.is_none()
+            && self.max_field_name_length.is_none()
+            && self.max_filename_length.is_none()
+            && self.field_constraints.is_empty()
+            && self.expected_boundary.is_none()
+            && !self.allow_missing_final_boundary
+            && self.on_field_size_exceeded.is_none()
+            && self.field_validator.is_none()
+    }
+
     pub(crate) fn is_it_allowed(&self, field: Option<&str>) -> bool {
         if let Some(ref allowed_fields) = self.allowed_fields {
             field
@@ -85,4 +569,55 @@ impl Constraints {
             true
         }
     }
+
+    pub(crate) fn unknown_field_action_for(&self, field: Option<&str>) -> UnknownFieldAction {
+        match &self.on_unknown_field {
+            Some(handler) => handler(field.unwrap_or_default()),
+            None => self.unknown_field_action,
+        }
+    }
+
+    pub(crate) fn is_ignored(&self, field: Option<&str>) -> bool {
+        field
+            .map(|field| self.ignored_fields.iter().any(|item| item == field))
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn field_constraints_for(&self, field: Option<&str>) -> Option<&FieldConstraints> {
+        field.and_then(|field| self.field_constraints.get(field))
+    }
+
+    pub(crate) fn size_limit_for(&self, field: Option<&str>, is_file: bool) -> u64 {
+        self.field_constraints_for(field)
+            .and_then(|constraints| constraints.max_size)
+            .unwrap_or_else(|| self.size_limit.extract_size_limit_for(field, is_file))
+    }
+
+    pub(crate) fn max_fields_for(&self, field: Option<&str>) -> Option<usize> {
+        field.and_then(|field| self.max_fields_per_name.get(field)).copied()
+    }
+}
+
+impl fmt::Debug for Constraints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Constraints")
+            .field("size_limit", &self.size_limit)
+            .field("allowed_fields", &self.allowed_fields)
+            .field("ignored_fields", &self.ignored_fields)
+            .field("unknown_field_action", &self.unknown_field_action)
+            .field("on_unknown_field", &self.on_unknown_field.is_some())
+            .field("max_fields_per_name", &self.max_fields_per_name)
+            .field("max_fields", &self.max_fields)
+            .field("max_field_name_length", &self.max_field_name_length)
+            .field("max_filename_length", &self.max_filename_length)
+            .field("field_constraints", &self.field_constraints)
+            .field("on_chunk_received", &self.on_chunk_received.is_some())
+            .field("version", &self.version)
+            .field("expected_boundary", &self.expected_boundary)
+            .field("allow_missing_final_boundary", &self.allow_missing_final_boundary)
+            .field("on_field_size_exceeded", &self.on_field_size_exceeded.is_some())
+            .field("field_validator", &self.field_validator.is_some())
+            .field("validation_failure_action", &self.validation_failure_action)
+            .finish()
+    }
 }
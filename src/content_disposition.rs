@@ -1,25 +1,183 @@
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::str::FromStr;
+
 use http::header::{self, HeaderMap};
 
-use crate::constants::ContentDispositionAttr;
+use crate::constants::{self, ContentDispositionAttr};
 
-#[derive(Debug)]
-pub(crate) struct ContentDisposition {
+/// A parsed [`Content-Disposition`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Disposition) header.
+///
+/// Besides describing multipart fields, this type can be used on its own to
+/// inspect any `Content-Disposition` header, e.g. the one of an HTTP download
+/// response.
+///
+/// # Examples
+///
+/// ```
+/// use multer::ContentDisposition;
+///
+/// let cd: ContentDisposition = r#"attachment; filename="report.pdf""#.parse().unwrap();
+///
+/// assert_eq!(cd.disposition_type(), Some("attachment"));
+/// assert_eq!(cd.file_name(), Some("report.pdf"));
+/// assert_eq!(cd.field_name(), None);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ContentDisposition {
+    pub(crate) raw: Option<Vec<u8>>,
     pub(crate) field_name: Option<String>,
     pub(crate) file_name: Option<String>,
 }
 
 impl ContentDisposition {
+    /// Parses the `Content-Disposition` header found in `headers`.
+    ///
+    /// If the header is missing, all the accessors return [`None`].
     pub fn parse(headers: &HeaderMap) -> ContentDisposition {
         let content_disposition = headers.get(header::CONTENT_DISPOSITION).map(|val| val.as_bytes());
+        ContentDisposition::from_raw(content_disposition)
+    }
 
+    pub(crate) fn from_raw(content_disposition: Option<&[u8]>) -> ContentDisposition {
         let field_name = content_disposition
             .and_then(|val| ContentDispositionAttr::Name.extract_from(val))
             .map(|attr| attr.into_owned());
 
+        // RFC 5987: `filename*` takes precedence over `filename`.
         let file_name = content_disposition
-            .and_then(|val| ContentDispositionAttr::FileName.extract_from(val))
+            .and_then(|val| {
+                ContentDispositionAttr::FileNameExt
+                    .extract_from(val)
+                    .or_else(|| ContentDispositionAttr::FileName.extract_from(val))
+            })
             .map(|attr| attr.into_owned());
 
-        ContentDisposition { field_name, file_name }
+        ContentDisposition {
+            raw: content_disposition.map(|val| val.to_vec()),
+            field_name,
+            file_name,
+        }
+    }
+
+    /// The disposition type, e.g. `form-data` or `attachment`.
+    pub fn disposition_type(&self) -> Option<&str> {
+        let raw = self.raw.as_deref()?;
+        let end = memchr::memchr(b';', raw).unwrap_or(raw.len());
+
+        std::str::from_utf8(&raw[..end])
+            .ok()
+            .map(str::trim)
+            .filter(|ty| !ty.is_empty())
+    }
+
+    /// The value of the `name` parameter.
+    pub fn field_name(&self) -> Option<&str> {
+        self.field_name.as_deref()
+    }
+
+    /// The value of the `filename` parameter, or the decoded value of the
+    /// [RFC 5987](https://tools.ietf.org/html/rfc5987) `filename*` parameter
+    /// which takes precedence over it.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// Iterate over all the parameters, excluding the leading disposition type.
+    ///
+    /// Values are returned with quotes removed and escaped quotes unescaped.
+    /// Parameters without a value or which aren't valid UTF-8 are skipped.
+    pub fn params(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        constants::params(self.raw.as_deref().unwrap_or_default())
+    }
+
+    /// Parses all the parameters of a raw `Content-Disposition` header value,
+    /// in order, excluding the leading disposition type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::ContentDisposition;
+    ///
+    /// let params = ContentDisposition::parse_all_params(br#"form-data; name="photo"; filename=a.png"#);
+    ///
+    /// assert_eq!(params[0], ("name".to_owned(), "photo".into()));
+    /// assert_eq!(params[1], ("filename".to_owned(), "a.png".into()));
+    /// ```
+    pub fn parse_all_params(header: &[u8]) -> Vec<(String, Cow<'_, str>)> {
+        ContentDispositionAttr::extract_all_from(header)
+    }
+
+    /// The value of an arbitrary parameter `name`, with quotes removed and
+    /// escaped quotes unescaped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::ContentDisposition;
+    ///
+    /// let cd: ContentDisposition = r#"form-data; name="photo"; size=1024"#.parse().unwrap();
+    ///
+    /// assert_eq!(cd.parse_param("size").as_deref(), Some("1024"));
+    /// assert_eq!(cd.parse_param("missing"), None);
+    /// ```
+    pub fn parse_param(&self, name: &str) -> Option<Cow<'_, str>> {
+        constants::extract_param(self.raw.as_deref()?, name.as_bytes())
+    }
+}
+
+impl FromStr for ContentDisposition {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ContentDisposition::from_raw(Some(s.as_bytes())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_content_disposition_parse() {
+        let mut headers = HeaderMap::new();
+        let cd = ContentDisposition::parse(&headers);
+        assert_eq!(cd.disposition_type(), None);
+        assert_eq!(cd.field_name(), None);
+        assert_eq!(cd.parse_param("name"), None);
+
+        headers.insert(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_static(r#"form-data; name="my_field"; filename="file abc.txt""#),
+        );
+
+        let cd = ContentDisposition::parse(&headers);
+        assert_eq!(cd.disposition_type(), Some("form-data"));
+        assert_eq!(cd.field_name(), Some("my_field"));
+        assert_eq!(cd.file_name(), Some("file abc.txt"));
+    }
+
+    #[test]
+    fn test_content_disposition_file_name_ext() {
+        let cd: ContentDisposition = "form-data; name=\"file\"; filename=\"fallback.txt\"; \
+            filename*=UTF-8''%E4%BD%A0%E5%A5%BD.txt"
+            .parse()
+            .unwrap();
+        assert_eq!(cd.field_name(), Some("file"));
+        assert_eq!(cd.file_name(), Some("你好.txt"));
+
+        let cd: ContentDisposition = "form-data; filename*=bogus; filename=\"fallback.txt\"".parse().unwrap();
+        assert_eq!(cd.file_name(), Some("fallback.txt"));
+    }
+
+    #[test]
+    fn test_content_disposition_parse_param() {
+        let cd: ContentDisposition = r#"attachment ; form=x; filename="a\"b.txt""#.parse().unwrap();
+        assert_eq!(cd.disposition_type(), Some("attachment"));
+        assert_eq!(cd.parse_param("form").as_deref(), Some("x"));
+        assert_eq!(cd.parse_param("filename").as_deref(), Some(r#"a"b.txt"#));
+        assert_eq!(cd.parse_param("name"), None);
     }
 }
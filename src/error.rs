@@ -1,5 +1,8 @@
 use std::fmt::{self, Debug, Display, Formatter};
 
+use crate::helpers::bytes_to_human;
+use crate::multipart::StreamingStage;
+
 type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
 /// A set of errors that can occur during parsing multipart stream and in other
@@ -27,8 +30,9 @@ pub enum Error {
     /// [`HeaderValue`](http::header::HeaderValue) type.
     DecodeHeaderValue { value: Vec<u8>, cause: BoxError },
 
-    /// Multipart stream is incomplete.
-    IncompleteStream,
+    /// Multipart stream is incomplete, it ended or is malformed at the given
+    /// parsing stage.
+    IncompleteStream { stage: StreamingStage },
 
     /// The incoming field size exceeded the maximum limit.
     FieldSizeExceeded { limit: u64, field_name: Option<String> },
@@ -36,9 +40,71 @@ pub enum Error {
     /// The incoming stream size exceeded the maximum limit.
     StreamSizeExceeded { limit: u64 },
 
+    /// The data preceding the first boundary exceeded the maximum limit.
+    PreambleSizeExceeded { limit: u64 },
+
+    /// The headers of a field exceeded the maximum limit.
+    HeaderSizeExceeded { limit: u64 },
+
+    /// A field name occurred more often than allowed by
+    /// [`constraints`](crate::Constraints::max_fields_per_name).
+    DuplicateFieldLimitExceeded { limit: usize, field_name: String },
+
+    /// The stream has more fields than allowed by
+    /// [`constraints`](crate::Constraints::max_fields).
+    FieldCountExceeded { limit: usize },
+
+    /// A field name is longer than allowed by
+    /// [`constraints`](crate::Constraints::max_field_name_length).
+    FieldNameTooLong { limit: usize, name: String },
+
+    /// A file name is longer than allowed by
+    /// [`constraints`](crate::Constraints::max_filename_length).
+    FileNameTooLong { limit: usize, name: String },
+
+    /// The field requested in
+    /// [`skip_to_field()`](crate::Multipart::skip_to_field) has already been
+    /// yielded.
+    FieldAlreadyConsumed {
+        requested_index: usize,
+        current_index: usize,
+    },
+
+    /// A field is missing the file name required by its
+    /// [`constraints`](crate::FieldConstraints::require_filename).
+    MissingFileName { field_name: Option<String> },
+
+    /// The content type of a field is not allowed by its
+    /// [`constraints`](crate::FieldConstraints::allowed_types).
+    ContentTypeNotAllowed {
+        field_name: Option<String>,
+        content_type: Option<String>,
+    },
+
+    /// A field was rejected by the
+    /// [`field validator`](crate::Constraints::with_field_validator).
+    FieldValidationFailed { field_name: Option<String> },
+
+    /// The field data size differs from the one expected in
+    /// [`field.verify_size()`](crate::Field::verify_size) method.
+    UnexpectedFieldSize { expected: u64, actual: u64 },
+
+    /// An environment variable read by
+    /// [`Constraints::new_from_env()`](crate::Constraints::new_from_env) has an
+    /// invalid value.
+    InvalidEnvVar { name: String, value: String },
+
     /// Stream read failed.
     StreamReadFailed(BoxError),
 
+    /// Reading the data of a field failed, after the given number of bytes
+    /// were received from the stream.
+    FieldReadFailed {
+        field_name: Option<String>,
+        offset: u64,
+        cause: Box<Error>,
+    },
+
     /// Failed to lock the multipart shared state for any changes.
     LockFailure,
 
@@ -51,6 +117,35 @@ pub enum Error {
     /// No boundary found in `Content-Type` header.
     NoBoundary,
 
+    /// The boundary given to
+    /// [`Constraints::for_boundary()`](crate::Constraints::for_boundary) is
+    /// not valid.
+    InvalidBoundary { boundary: String },
+
+    /// The boundary of the [`Multipart`](crate::Multipart) differs from the
+    /// one expected by its
+    /// [`constraints`](crate::Constraints::for_boundary).
+    BoundaryMismatch { expected: String, actual: String },
+
+    /// Failed to decode the field data as text in the given encoding in
+    /// [`field.text_streaming()`](crate::Field::text_streaming) method.
+    DecodeText { encoding: String },
+
+    /// Failed to decode the field data in the given
+    /// `Content-Transfer-Encoding` in
+    /// [`field.decoded_bytes()`](crate::Field::decoded_bytes) method.
+    DecodeTransferEncoding { encoding: String },
+
+    /// A required field was not found in the stream.
+    MissingField { field_name: String },
+
+    /// Failed to write the field data.
+    WriteFailed(std::io::Error),
+
+    /// Another error with some context added by
+    /// [`Error::context()`](crate::Error::context).
+    Context { message: String, source: Box<Error> },
+
     /// Failed to decode the field data as `JSON` in
     /// [`field.json()`](crate::Field::json) method.
     #[cfg(feature = "json")]
@@ -58,9 +153,346 @@ pub enum Error {
     DecodeJson(serde_json::Error),
 }
 
+impl Error {
+    /// Get the underlying error wrapped by this error, e.g. the error of the
+    /// source stream in [`Error::StreamReadFailed`], to downcast it.
+    ///
+    /// Errors with added [`context()`](Self::context) return the underlying
+    /// error of the error they wrap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// let err = multer::Error::StreamReadFailed(Box::new(io::Error::new(io::ErrorKind::Other, "reset")));
+    ///
+    /// let io_err = err.inner_error().unwrap().downcast_ref::<io::Error>().unwrap();
+    /// assert_eq!(io_err.kind(), io::ErrorKind::Other);
+    /// ```
+    pub fn inner_error(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        match self {
+            Error::StreamReadFailed(e) => Some(e.as_ref()),
+            Error::DecodeHeaderName { cause, .. } => Some(cause.as_ref()),
+            Error::DecodeHeaderValue { cause, .. } => Some(cause.as_ref()),
+            Error::ReadHeaderFailed(e) => Some(e),
+            Error::DecodeContentType(e) => Some(e),
+            Error::WriteFailed(e) => Some(e),
+            Error::FieldReadFailed { cause, .. } => cause.inner_error(),
+            Error::Context { source, .. } => source.inner_error(),
+            #[cfg(feature = "json")]
+            Error::DecodeJson(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Converts this error into the underlying error it wraps, if any.
+    ///
+    /// See [`inner_error()`](Self::inner_error) for details.
+    pub fn into_inner(self) -> Option<BoxError> {
+        match self {
+            Error::StreamReadFailed(e) => Some(e),
+            Error::DecodeHeaderName { cause, .. } => Some(cause),
+            Error::DecodeHeaderValue { cause, .. } => Some(cause),
+            Error::ReadHeaderFailed(e) => Some(Box::new(e)),
+            Error::DecodeContentType(e) => Some(Box::new(e)),
+            Error::WriteFailed(e) => Some(Box::new(e)),
+            Error::FieldReadFailed { cause, .. } => cause.into_inner(),
+            Error::Context { source, .. } => source.into_inner(),
+            #[cfg(feature = "json")]
+            Error::DecodeJson(e) => Some(Box::new(e)),
+            _ => None,
+        }
+    }
+
+    /// Whether the parsing can continue with the next field after this
+    /// error, i.e. the field was rejected by the constraints.
+    ///
+    /// [`Error::LockFailure`] is not recoverable: it's caused by the caller
+    /// still holding a field, and retrying would fail the same way.
+    pub(crate) fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            Error::UnknownField { .. }
+                | Error::FieldNameTooLong { .. }
+                | Error::FileNameTooLong { .. }
+                | Error::DuplicateFieldLimitExceeded { .. }
+                | Error::MissingFileName { .. }
+                | Error::ContentTypeNotAllowed { .. }
+                | Error::FieldValidationFailed { .. }
+        )
+    }
+
+    /// Wraps this error with a message describing what was being done when
+    /// it occurred.
+    ///
+    /// The original error is available via
+    /// [`source()`](std::error::Error::source).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::error::Error as _;
+    ///
+    /// let err = multer::Error::MissingField {
+    ///     field_name: "avatar".to_owned(),
+    /// }
+    /// .context("processing profile");
+    ///
+    /// assert_eq!(err.to_string(), "processing profile: missing required field: \"avatar\"");
+    /// assert_eq!(err.source().unwrap().to_string(), "missing required field: \"avatar\"");
+    /// ```
+    pub fn context<M: Display>(self, message: M) -> Error {
+        Error::Context {
+            message: message.to_string(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Get the category of this error, to handle errors without matching
+    /// every variant.
+    ///
+    /// Errors with added [`context()`](Self::context) and
+    /// [`Error::FieldReadFailed`] return the kind of the error they wrap.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::ErrorKind;
+    ///
+    /// let err = multer::Error::StreamSizeExceeded { limit: 1024 };
+    /// assert_eq!(err.kind(), ErrorKind::SizeExceeded);
+    ///
+    /// let err = err.context("reading upload");
+    /// assert_eq!(err.kind(), ErrorKind::SizeExceeded);
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::UnknownField { .. } => ErrorKind::UnknownField,
+            Error::FieldSizeExceeded { .. }
+            | Error::StreamSizeExceeded { .. }
+            | Error::PreambleSizeExceeded { .. }
+            | Error::HeaderSizeExceeded { .. }
+            | Error::DuplicateFieldLimitExceeded { .. }
+            | Error::FieldCountExceeded { .. }
+            | Error::FieldNameTooLong { .. }
+            | Error::FileNameTooLong { .. } => ErrorKind::SizeExceeded,
+            Error::MissingFileName { .. }
+            | Error::ContentTypeNotAllowed { .. }
+            | Error::FieldValidationFailed { .. }
+            | Error::MissingField { .. }
+            | Error::UnexpectedFieldSize { .. }
+            | Error::BoundaryMismatch { .. } => ErrorKind::ConstraintViolated,
+            Error::IncompleteFieldData { .. } | Error::IncompleteHeaders | Error::IncompleteStream { .. } => {
+                ErrorKind::IncompleteData
+            }
+            Error::ReadHeaderFailed(_)
+            | Error::DecodeHeaderName { .. }
+            | Error::DecodeHeaderValue { .. }
+            | Error::NoMultipart
+            | Error::DecodeContentType(_)
+            | Error::NoBoundary
+            | Error::DecodeText { .. }
+            | Error::DecodeTransferEncoding { .. } => ErrorKind::ParseFailure,
+            #[cfg(feature = "json")]
+            Error::DecodeJson(_) => ErrorKind::ParseFailure,
+            Error::StreamReadFailed(_) => ErrorKind::StreamFailure,
+            Error::WriteFailed(_) => ErrorKind::WriteFailure,
+            Error::FieldReadFailed { cause, .. } => cause.kind(),
+            Error::Context { source, .. } => source.kind(),
+            Error::FieldAlreadyConsumed { .. }
+            | Error::InvalidEnvVar { .. }
+            | Error::LockFailure
+            | Error::InvalidBoundary { .. } => ErrorKind::Other,
+        }
+    }
+}
+
+/// The category of an [`Error`], returned by [`Error::kind()`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// A field is not allowed by the
+    /// [`constraints`](crate::Constraints::allowed_fields).
+    UnknownField,
+
+    /// The stream, a field or a part of it exceeded a limit.
+    SizeExceeded,
+
+    /// A field or the stream broke another rule of the
+    /// [`constraints`](crate::Constraints).
+    ConstraintViolated,
+
+    /// The stream, a field or its headers ended unexpectedly.
+    IncompleteData,
+
+    /// The stream, the headers or the field data couldn't be parsed or
+    /// decoded.
+    ParseFailure,
+
+    /// Reading the source stream failed.
+    StreamFailure,
+
+    /// Writing the field data failed.
+    WriteFailure,
+
+    /// Any other error, like a misuse of the API or an invalid configuration.
+    Other,
+}
+
+/// Shows the variant and its fields.
+///
+/// # Examples
+///
+/// ```
+/// let err = multer::Error::FieldSizeExceeded {
+///     limit: 1024,
+///     field_name: Some("avatar".to_owned()),
+/// };
+///
+/// assert_eq!(
+///     format!("{:?}", err),
+///     "Error::FieldSizeExceeded { limit: 1024, field_name: Some(\"avatar\") }"
+/// );
+/// ```
 impl Debug for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        Display::fmt(self, f)
+        match self {
+            Error::UnknownField { field_name } => f
+                .debug_struct("Error::UnknownField")
+                .field("field_name", field_name)
+                .finish(),
+            Error::IncompleteFieldData { field_name } => f
+                .debug_struct("Error::IncompleteFieldData")
+                .field("field_name", field_name)
+                .finish(),
+            Error::IncompleteHeaders => f.write_str("Error::IncompleteHeaders"),
+            Error::ReadHeaderFailed(e) => f.debug_tuple("Error::ReadHeaderFailed").field(e).finish(),
+            Error::DecodeHeaderName { name, cause } => f
+                .debug_struct("Error::DecodeHeaderName")
+                .field("name", name)
+                .field("cause", cause)
+                .finish(),
+            Error::DecodeHeaderValue { value, cause } => f
+                .debug_struct("Error::DecodeHeaderValue")
+                .field("value", value)
+                .field("cause", cause)
+                .finish(),
+            Error::IncompleteStream { stage } => {
+                f.debug_struct("Error::IncompleteStream").field("stage", stage).finish()
+            }
+            Error::FieldSizeExceeded { limit, field_name } => f
+                .debug_struct("Error::FieldSizeExceeded")
+                .field("limit", limit)
+                .field("field_name", field_name)
+                .finish(),
+            Error::StreamSizeExceeded { limit } => f
+                .debug_struct("Error::StreamSizeExceeded")
+                .field("limit", limit)
+                .finish(),
+            Error::PreambleSizeExceeded { limit } => f
+                .debug_struct("Error::PreambleSizeExceeded")
+                .field("limit", limit)
+                .finish(),
+            Error::HeaderSizeExceeded { limit } => f
+                .debug_struct("Error::HeaderSizeExceeded")
+                .field("limit", limit)
+                .finish(),
+            Error::FieldCountExceeded { limit } => f
+                .debug_struct("Error::FieldCountExceeded")
+                .field("limit", limit)
+                .finish(),
+            Error::FieldNameTooLong { limit, name } => f
+                .debug_struct("Error::FieldNameTooLong")
+                .field("limit", limit)
+                .field("name", name)
+                .finish(),
+            Error::FileNameTooLong { limit, name } => f
+                .debug_struct("Error::FileNameTooLong")
+                .field("limit", limit)
+                .field("name", name)
+                .finish(),
+            Error::DuplicateFieldLimitExceeded { limit, field_name } => f
+                .debug_struct("Error::DuplicateFieldLimitExceeded")
+                .field("limit", limit)
+                .field("field_name", field_name)
+                .finish(),
+            Error::FieldAlreadyConsumed {
+                requested_index,
+                current_index,
+            } => f
+                .debug_struct("Error::FieldAlreadyConsumed")
+                .field("requested_index", requested_index)
+                .field("current_index", current_index)
+                .finish(),
+            Error::MissingFileName { field_name } => f
+                .debug_struct("Error::MissingFileName")
+                .field("field_name", field_name)
+                .finish(),
+            Error::ContentTypeNotAllowed {
+                field_name,
+                content_type,
+            } => f
+                .debug_struct("Error::ContentTypeNotAllowed")
+                .field("field_name", field_name)
+                .field("content_type", content_type)
+                .finish(),
+            Error::FieldValidationFailed { field_name } => f
+                .debug_struct("Error::FieldValidationFailed")
+                .field("field_name", field_name)
+                .finish(),
+            Error::UnexpectedFieldSize { expected, actual } => f
+                .debug_struct("Error::UnexpectedFieldSize")
+                .field("expected", expected)
+                .field("actual", actual)
+                .finish(),
+            Error::InvalidEnvVar { name, value } => f
+                .debug_struct("Error::InvalidEnvVar")
+                .field("name", name)
+                .field("value", value)
+                .finish(),
+            Error::StreamReadFailed(e) => f.debug_tuple("Error::StreamReadFailed").field(e).finish(),
+            Error::FieldReadFailed {
+                field_name,
+                offset,
+                cause,
+            } => f
+                .debug_struct("Error::FieldReadFailed")
+                .field("field_name", field_name)
+                .field("offset", offset)
+                .field("cause", cause)
+                .finish(),
+            Error::LockFailure => f.write_str("Error::LockFailure"),
+            Error::NoMultipart => f.write_str("Error::NoMultipart"),
+            Error::DecodeContentType(e) => f.debug_tuple("Error::DecodeContentType").field(e).finish(),
+            Error::NoBoundary => f.write_str("Error::NoBoundary"),
+            Error::InvalidBoundary { boundary } => f
+                .debug_struct("Error::InvalidBoundary")
+                .field("boundary", boundary)
+                .finish(),
+            Error::BoundaryMismatch { expected, actual } => f
+                .debug_struct("Error::BoundaryMismatch")
+                .field("expected", expected)
+                .field("actual", actual)
+                .finish(),
+            Error::DecodeText { encoding } => f.debug_struct("Error::DecodeText").field("encoding", encoding).finish(),
+            Error::DecodeTransferEncoding { encoding } => f
+                .debug_struct("Error::DecodeTransferEncoding")
+                .field("encoding", encoding)
+                .finish(),
+            Error::MissingField { field_name } => f
+                .debug_struct("Error::MissingField")
+                .field("field_name", field_name)
+                .finish(),
+            Error::WriteFailed(e) => f.debug_tuple("Error::WriteFailed").field(e).finish(),
+            Error::Context { message, source } => f
+                .debug_struct("Error::Context")
+                .field("message", message)
+                .field("source", source)
+                .finish(),
+            #[cfg(feature = "json")]
+            Error::DecodeJson(e) => f.debug_tuple("Error::DecodeJson").field(e).finish(),
+        }
     }
 }
 
@@ -83,19 +515,102 @@ impl Display for Error {
             }
             Error::FieldSizeExceeded { limit, field_name } => {
                 let name = field_name.as_deref().unwrap_or("<unknown>");
-                write!(f, "field {:?} exceeded the size limit: {} bytes", name, limit)
+                write!(
+                    f,
+                    "field {:?} exceeded the size limit: {}",
+                    name,
+                    bytes_to_human(*limit)
+                )
             }
             Error::StreamSizeExceeded { limit } => {
-                write!(f, "stream size exceeded limit: {} bytes", limit)
+                write!(f, "stream size exceeded limit: {}", bytes_to_human(*limit))
+            }
+            Error::PreambleSizeExceeded { limit } => {
+                write!(f, "preamble size exceeded limit: {}", bytes_to_human(*limit))
+            }
+            Error::HeaderSizeExceeded { limit } => {
+                write!(f, "field headers size exceeded limit: {}", bytes_to_human(*limit))
+            }
+            Error::FieldCountExceeded { limit } => {
+                write!(f, "field count exceeded limit: {}", limit)
+            }
+            Error::FieldNameTooLong { limit, name } => {
+                write!(f, "field name {:?} exceeded length limit: {}", name, limit)
+            }
+            Error::FileNameTooLong { limit, name } => {
+                write!(f, "file name {:?} exceeded length limit: {}", name, limit)
+            }
+            Error::DuplicateFieldLimitExceeded { limit, field_name } => {
+                write!(f, "field {:?} exceeded the occurrence limit: {}", field_name, limit)
+            }
+            Error::FieldAlreadyConsumed {
+                requested_index,
+                current_index,
+            } => {
+                write!(
+                    f,
+                    "field at index {} already consumed, next field index is {}",
+                    requested_index, current_index
+                )
+            }
+            Error::MissingFileName { field_name } => {
+                let name = field_name.as_deref().unwrap_or("<unknown>");
+                write!(f, "field {:?} received without a file name", name)
+            }
+            Error::ContentTypeNotAllowed {
+                field_name,
+                content_type,
+            } => {
+                let name = field_name.as_deref().unwrap_or("<unknown>");
+                let content_type = content_type.as_deref().unwrap_or("<none>");
+                write!(
+                    f,
+                    "field {:?} received with disallowed content type: {}",
+                    name, content_type
+                )
+            }
+            Error::FieldValidationFailed { field_name } => {
+                let name = field_name.as_deref().unwrap_or("<unknown>");
+                write!(f, "field {:?} rejected by the field validator", name)
+            }
+            Error::UnexpectedFieldSize { expected, actual } => {
+                write!(f, "expected field size of {} bytes, found {} bytes", expected, actual)
+            }
+            Error::InvalidEnvVar { name, value } => {
+                write!(f, "invalid value for environment variable {}: {:?}", name, value)
             }
             Error::ReadHeaderFailed(_) => write!(f, "failed to read headers"),
             Error::StreamReadFailed(_) => write!(f, "failed to read stream"),
+            Error::FieldReadFailed {
+                field_name,
+                offset,
+                cause,
+            } => write!(
+                f,
+                "failed to read field {:?} at stream offset {}: {}",
+                field_name, offset, cause
+            ),
             Error::DecodeContentType(_) => write!(f, "failed to decode Content-Type"),
             Error::IncompleteHeaders => write!(f, "failed to read field complete headers"),
-            Error::IncompleteStream => write!(f, "incomplete multipart stream"),
+            Error::IncompleteStream { stage } => {
+                write!(f, "incomplete multipart stream (ended during {:?})", stage)
+            }
             Error::LockFailure => write!(f, "failed to lock multipart state"),
             Error::NoMultipart => write!(f, "Content-Type is not multipart/form-data"),
             Error::NoBoundary => write!(f, "multipart boundary not found in Content-Type"),
+            Error::InvalidBoundary { boundary } => write!(f, "invalid multipart boundary: {:?}", boundary),
+            Error::BoundaryMismatch { expected, actual } => {
+                write!(f, "expected multipart boundary {:?}, found {:?}", expected, actual)
+            }
+            Error::DecodeText { encoding } => {
+                write!(f, "failed to decode field data as {} text", encoding)
+            }
+            Error::DecodeTransferEncoding { encoding } => {
+                write!(f, "failed to decode field data with transfer encoding {:?}", encoding)
+            }
+            Error::MissingField { field_name } => write!(f, "missing required field: {:?}", field_name),
+            Error::WriteFailed(_) => write!(f, "failed to write field data"),
+            Error::Context { message, source } => write!(f, "{}: {}", message, source),
             #[cfg(feature = "json")]
             Error::DecodeJson(_) => write!(f, "failed to decode field data as JSON"),
         }
@@ -110,24 +625,185 @@ impl std::error::Error for Error {
             Error::DecodeHeaderValue { cause, .. } => Some(cause.as_ref()),
             Error::StreamReadFailed(e) => Some(e.as_ref()),
             Error::DecodeContentType(e) => Some(e),
+            Error::WriteFailed(e) => Some(e),
+            Error::FieldReadFailed { cause, .. } => Some(cause.as_ref()),
+            Error::Context { source, .. } => Some(source.as_ref()),
             #[cfg(feature = "json")]
             Error::DecodeJson(e) => Some(e),
             Error::UnknownField { .. }
             | Error::IncompleteFieldData { .. }
             | Error::IncompleteHeaders
-            | Error::IncompleteStream
+            | Error::IncompleteStream { .. }
             | Error::FieldSizeExceeded { .. }
             | Error::StreamSizeExceeded { .. }
+            | Error::PreambleSizeExceeded { .. }
+            | Error::HeaderSizeExceeded { .. }
+            | Error::DecodeText { .. }
+            | Error::DecodeTransferEncoding { .. }
+            | Error::MissingField { .. }
+            | Error::DuplicateFieldLimitExceeded { .. }
+            | Error::FieldCountExceeded { .. }
+            | Error::FieldNameTooLong { .. }
+            | Error::FileNameTooLong { .. }
+            | Error::FieldAlreadyConsumed { .. }
+            | Error::MissingFileName { .. }
+            | Error::ContentTypeNotAllowed { .. }
+            | Error::FieldValidationFailed { .. }
+            | Error::UnexpectedFieldSize { .. }
+            | Error::InvalidEnvVar { .. }
             | Error::LockFailure
             | Error::NoMultipart
-            | Error::NoBoundary => None,
+            | Error::NoBoundary
+            | Error::InvalidBoundary { .. }
+            | Error::BoundaryMismatch { .. } => None,
         }
     }
 }
 
+/// Errors are equal if they are the same variant with equal fields. The
+/// wrapped errors of other crates are compared by their messages.
+///
+/// # Examples
+///
+/// ```
+/// use multer::Error;
+///
+/// assert_eq!(Error::StreamSizeExceeded { limit: 1024 }, Error::StreamSizeExceeded { limit: 1024 });
+/// assert_ne!(Error::StreamSizeExceeded { limit: 1024 }, Error::StreamSizeExceeded { limit: 2048 });
+/// assert_ne!(
+///     Error::StreamSizeExceeded { limit: 1024 },
+///     Error::PreambleSizeExceeded { limit: 1024 }
+/// );
+/// ```
 impl PartialEq for Error {
     fn eq(&self, other: &Self) -> bool {
-        self.to_string().eq(&other.to_string())
+        fn same_message(a: &dyn Display, b: &dyn Display) -> bool {
+            a.to_string() == b.to_string()
+        }
+
+        match (self, other) {
+            (Error::UnknownField { field_name: a }, Error::UnknownField { field_name: b }) => a == b,
+            (Error::IncompleteFieldData { field_name: a }, Error::IncompleteFieldData { field_name: b }) => a == b,
+            (Error::IncompleteHeaders, Error::IncompleteHeaders) => true,
+            (Error::ReadHeaderFailed(a), Error::ReadHeaderFailed(b)) => a == b,
+            (Error::DecodeHeaderName { name: a, cause: ac }, Error::DecodeHeaderName { name: b, cause: bc }) => {
+                a == b && same_message(ac, bc)
+            }
+            (Error::DecodeHeaderValue { value: a, cause: ac }, Error::DecodeHeaderValue { value: b, cause: bc }) => {
+                a == b && same_message(ac, bc)
+            }
+            (Error::IncompleteStream { stage: a }, Error::IncompleteStream { stage: b }) => a == b,
+            (
+                Error::FieldSizeExceeded {
+                    limit: a,
+                    field_name: an,
+                },
+                Error::FieldSizeExceeded {
+                    limit: b,
+                    field_name: bn,
+                },
+            ) => a == b && an == bn,
+            (Error::StreamSizeExceeded { limit: a }, Error::StreamSizeExceeded { limit: b }) => a == b,
+            (Error::PreambleSizeExceeded { limit: a }, Error::PreambleSizeExceeded { limit: b }) => a == b,
+            (Error::HeaderSizeExceeded { limit: a }, Error::HeaderSizeExceeded { limit: b }) => a == b,
+            (
+                Error::DuplicateFieldLimitExceeded {
+                    limit: a,
+                    field_name: an,
+                },
+                Error::DuplicateFieldLimitExceeded {
+                    limit: b,
+                    field_name: bn,
+                },
+            ) => a == b && an == bn,
+            (Error::FieldCountExceeded { limit: a }, Error::FieldCountExceeded { limit: b }) => a == b,
+            (Error::FieldNameTooLong { limit: a, name: an }, Error::FieldNameTooLong { limit: b, name: bn }) => {
+                a == b && an == bn
+            }
+            (Error::FileNameTooLong { limit: a, name: an }, Error::FileNameTooLong { limit: b, name: bn }) => {
+                a == b && an == bn
+            }
+            (
+                Error::FieldAlreadyConsumed {
+                    requested_index: a,
+                    current_index: ac,
+                },
+                Error::FieldAlreadyConsumed {
+                    requested_index: b,
+                    current_index: bc,
+                },
+            ) => a == b && ac == bc,
+            (Error::MissingFileName { field_name: a }, Error::MissingFileName { field_name: b }) => a == b,
+            (
+                Error::ContentTypeNotAllowed {
+                    field_name: a,
+                    content_type: at,
+                },
+                Error::ContentTypeNotAllowed {
+                    field_name: b,
+                    content_type: bt,
+                },
+            ) => a == b && at == bt,
+            (Error::FieldValidationFailed { field_name: a }, Error::FieldValidationFailed { field_name: b }) => a == b,
+            (
+                Error::UnexpectedFieldSize {
+                    expected: a,
+                    actual: aa,
+                },
+                Error::UnexpectedFieldSize {
+                    expected: b,
+                    actual: ba,
+                },
+            ) => a == b && aa == ba,
+            (Error::InvalidEnvVar { name: a, value: av }, Error::InvalidEnvVar { name: b, value: bv }) => {
+                a == b && av == bv
+            }
+            (Error::StreamReadFailed(a), Error::StreamReadFailed(b)) => same_message(a, b),
+            (
+                Error::FieldReadFailed {
+                    field_name: a,
+                    offset: ao,
+                    cause: ac,
+                },
+                Error::FieldReadFailed {
+                    field_name: b,
+                    offset: bo,
+                    cause: bc,
+                },
+            ) => a == b && ao == bo && ac == bc,
+            (Error::LockFailure, Error::LockFailure) => true,
+            (Error::NoMultipart, Error::NoMultipart) => true,
+            (Error::DecodeContentType(a), Error::DecodeContentType(b)) => same_message(a, b),
+            (Error::NoBoundary, Error::NoBoundary) => true,
+            (Error::InvalidBoundary { boundary: a }, Error::InvalidBoundary { boundary: b }) => a == b,
+            (
+                Error::BoundaryMismatch {
+                    expected: a,
+                    actual: aa,
+                },
+                Error::BoundaryMismatch {
+                    expected: b,
+                    actual: ba,
+                },
+            ) => a == b && aa == ba,
+            (Error::DecodeText { encoding: a }, Error::DecodeText { encoding: b }) => a == b,
+            (Error::DecodeTransferEncoding { encoding: a }, Error::DecodeTransferEncoding { encoding: b }) => a == b,
+            (Error::MissingField { field_name: a }, Error::MissingField { field_name: b }) => a == b,
+            (Error::WriteFailed(a), Error::WriteFailed(b)) => a.kind() == b.kind() && same_message(a, b),
+            (
+                Error::Context {
+                    message: a,
+                    source: asrc,
+                },
+                Error::Context {
+                    message: b,
+                    source: bsrc,
+                },
+            ) => a == b && asrc == bsrc,
+            #[cfg(feature = "json")]
+            (Error::DecodeJson(a), Error::DecodeJson(b)) => same_message(a, b),
+            _ => false,
+        }
     }
 }
 
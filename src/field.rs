@@ -1,17 +1,26 @@
+use std::borrow::Cow;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use bytes::{Bytes, BytesMut};
 use encoding_rs::{Encoding, UTF_8};
-use futures_util::stream::{Stream, TryStreamExt};
+use futures_util::future;
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
 use http::header::HeaderMap;
 #[cfg(feature = "json")]
 use serde::de::DeserializeOwned;
 use spin::mutex::spin::SpinMutex as Mutex;
+#[cfg(feature = "tokio-io")]
+use {
+    tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    tokio_util::io::StreamReader,
+};
 
 use crate::content_disposition::ContentDisposition;
-use crate::multipart::{MultipartState, StreamingStage};
+use crate::form_part::FormPart;
+use crate::multipart::{Multipart, MultipartState, StreamingStage};
+use crate::size_exceeded_action::SizeExceededAction;
 use crate::{helpers, Error};
 
 /// A single field in a multipart stream.
@@ -42,7 +51,7 @@ use crate::{helpers, Error};
 /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
 ///
 /// while let Some(field) = multipart.next_field().await.unwrap() {
-///     let content = field.text().await.unwrap();
+///     let content = field.read_to_string().await.unwrap();
 ///     assert_eq!(content, "abcd");
 /// }
 /// # }
@@ -88,11 +97,121 @@ impl<'r> Field<'r> {
         self.content_disposition.file_name.as_deref()
     }
 
+    /// The extension of the [`file_name()`](Self::file_name), without the
+    /// leading `.`, e.g. `txt` for `a-text-file.txt`.
+    ///
+    /// Returns `None` if the file name has no extension or is a hidden file
+    /// like `.bashrc`. Only `/` is treated as a path separator, as the file
+    /// name comes from the client and not from the local file system.
+    pub fn file_extension(&self) -> Option<&str> {
+        self.file_name().and_then(helpers::file_extension)
+    }
+
+    /// The disposition type found in the [`Content-Disposition`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Disposition) header,
+    /// e.g. `form-data`, or `attachment` and `inline` in `multipart/mixed`
+    /// streams.
+    pub fn content_disposition_type(&self) -> Option<&str> {
+        self.content_disposition.disposition_type()
+    }
+
+    /// All the parameters found in the [`Content-Disposition`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Disposition) header,
+    /// including `name` and `filename`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; \
+    ///     filename=\"a.txt\"; size=4\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     for (name, value) in field.content_disposition_params() {
+    ///         println!("{}: {}", name, value);
+    ///     }
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn content_disposition_params(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        self.content_disposition.params()
+    }
+
+    /// The parsed [`Content-Disposition`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Disposition) header,
+    /// e.g. to look up the [RFC 2183](https://tools.ietf.org/html/rfc2183)
+    /// `creation-date` or `modification-date` parameters of `multipart/mixed`
+    /// parts with [`ContentDisposition::parse_param()`].
+    pub fn content_disposition(&self) -> &ContentDisposition {
+        &self.content_disposition
+    }
+
     /// Get the content type of the field.
     pub fn content_type(&self) -> Option<&mime::Mime> {
         self.content_type.as_ref()
     }
 
+    /// Get the raw value of the field's `Content-Type` header, without parsing
+    /// it as [`mime::Mime`].
+    ///
+    /// Unlike [`content_type()`](Self::content_type), this is available even
+    /// if the header value is not a valid MIME type.
+    pub fn raw_content_type_str(&self) -> Option<&str> {
+        self.headers
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|val| val.to_str().ok())
+    }
+
+    /// Get the value of the field's `Content-Transfer-Encoding` header, e.g.
+    /// `base64` or `quoted-printable`.
+    ///
+    /// Browsers don't set it, but other clients may. The field data is not
+    /// decoded by [`read_to_end()`](Self::read_to_end) or the [`Stream`] API,
+    /// see [`decoded_bytes()`](Self::decoded_bytes).
+    pub fn content_transfer_encoding(&self) -> Option<&str> {
+        self.headers
+            .get("content-transfer-encoding")
+            .and_then(|val| val.to_str().ok())
+            .map(str::trim)
+    }
+
+    /// Get the `charset` parameter of the field's `Content-Type` header, or
+    /// `default` if not presented.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\
+    ///     Content-Type: text/plain; charset=iso-8859-1\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.charset_or_default("utf-8"), "iso-8859-1");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn charset_or_default<'a>(&'a self, default: &'a str) -> &'a str {
+        self.content_type()
+            .and_then(|mime| mime.get_param(mime::CHARSET))
+            .map(|charset| charset.as_str())
+            .unwrap_or(default)
+    }
+
     /// Get a map of headers as [`HeaderMap`].
     pub fn headers(&self) -> &HeaderMap {
         &self.headers
@@ -116,13 +235,13 @@ impl<'r> Field<'r> {
     /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
     ///
     /// while let Some(field) = multipart.next_field().await.unwrap() {
-    ///     let bytes = field.bytes().await.unwrap();
+    ///     let bytes = field.read_to_end().await.unwrap();
     ///     assert_eq!(bytes.len(), 4);
     /// }
     /// # }
     /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
     /// ```
-    pub async fn bytes(self) -> crate::Result<Bytes> {
+    pub async fn read_to_end(self) -> crate::Result<Bytes> {
         let mut buf = BytesMut::new();
 
         let mut this = self;
@@ -133,10 +252,510 @@ impl<'r> Field<'r> {
         Ok(buf.freeze())
     }
 
+    /// Get the full data of the field as [`Bytes`], decoded according to its
+    /// [`Content-Transfer-Encoding`](Self::content_transfer_encoding).
+    ///
+    /// `base64` and `quoted-printable` data is decoded, while `7bit`, `8bit`
+    /// and `binary` data, or data without the header, is returned as is.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `transfer-encoding` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"\r\nContent-Transfer-Encoding: base64\r\n\r\nSGVsbG8gd29ybGQ=\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let field = multipart.next_field().await.unwrap().unwrap();
+    /// assert_eq!(field.decoded_bytes().await.unwrap(), "Hello world");
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::DecodeTransferEncoding`] if the data is
+    /// malformed or the encoding is not supported.
+    #[cfg(feature = "transfer-encoding")]
+    #[cfg_attr(nightly, doc(cfg(feature = "transfer-encoding")))]
+    pub async fn decoded_bytes(self) -> crate::Result<Bytes> {
+        let encoding = match self.content_transfer_encoding() {
+            Some(encoding) => encoding.to_owned(),
+            None => return self.read_to_end().await,
+        };
+
+        let data = self.read_to_end().await?;
+        helpers::decode_transfer_encoding(&encoding, data).ok_or(Error::DecodeTransferEncoding { encoding })
+    }
+
+    /// Get the full data of the field as [`Bytes`].
+    #[deprecated(note = "renamed to `read_to_end()`")]
+    pub async fn bytes(self) -> crate::Result<Bytes> {
+        self.read_to_end().await
+    }
+
+    /// Reads the full data of the field and converts it into a [`FormPart`],
+    /// which can be kept after the [`Multipart`](crate::Multipart) stream is
+    /// dropped.
+    pub async fn into_form_part(self) -> crate::Result<FormPart> {
+        let headers = self.headers.clone();
+        let content_disposition = self.content_disposition.clone();
+        let content_type = self.content_type.clone();
+        let idx = self.idx;
+
+        Ok(FormPart {
+            headers,
+            content_disposition,
+            content_type,
+            idx,
+            data: self.read_to_end().await?,
+        })
+    }
+
+    /// Converts the field into an [`http::Response`] with the field headers,
+    /// like `Content-Type` and `Content-Disposition`, as its headers and the
+    /// field data as its body.
+    ///
+    /// This is useful to forward a received field as an independent HTTP
+    /// message, e.g. in upload proxies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use http::header::CONTENT_TYPE;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a.txt\"\r\n\
+    ///     Content-Type: text/plain\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let field = multipart.next_field().await.unwrap().unwrap();
+    /// let response = field.into_http_response().await.unwrap();
+    ///
+    /// assert_eq!(response.headers()[CONTENT_TYPE], "text/plain");
+    /// assert_eq!(response.body(), "abcd");
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn into_http_response(self) -> crate::Result<http::Response<Bytes>> {
+        let headers = self.headers.clone();
+        let mut response = http::Response::new(self.read_to_end().await?);
+        *response.headers_mut() = headers;
+
+        Ok(response)
+    }
+
+    /// Get the full data of the field as [`Bytes`] along with the name of its
+    /// detected text encoding, e.g. `UTF-8` or `windows-1252`.
+    ///
+    /// The encoding is detected from the byte order mark if present, and
+    /// guessed from the content otherwise. The `charset` parameter of the
+    /// `Content-Type` header is ignored. The name can be passed to
+    /// [`Encoding::for_label()`] to decode the data.
+    ///
+    /// This is useful for text uploads, like CSV files, whose encoding isn't
+    /// specified.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `encoding-detection` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a.csv\"\r\n\r\n\
+    ///     \u{feff}name,city\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let (_, encoding) = field.into_bytes_with_encoding_detection().await.unwrap();
+    ///     assert_eq!(encoding, "UTF-8");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "encoding-detection")]
+    #[cfg_attr(nightly, doc(cfg(feature = "encoding-detection")))]
+    pub async fn into_bytes_with_encoding_detection(self) -> crate::Result<(Bytes, &'static str)> {
+        let bytes = self.read_to_end().await?;
+
+        let encoding = match Encoding::for_bom(&bytes) {
+            Some((encoding, _)) => encoding,
+            None => {
+                let mut detector = chardetng::EncodingDetector::new();
+                detector.feed(&bytes, true);
+                detector.guess(None, true)
+            }
+        };
+
+        Ok((bytes, encoding.name()))
+    }
+
+    /// Get the full data of the field as [`Bytes`] and transform it with `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let checksum = field
+    ///         .map_bytes(|bytes| Ok(bytes.iter().map(|b| *b as u32).sum::<u32>()))
+    ///         .await
+    ///         .unwrap();
+    ///     assert_eq!(checksum, 394);
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn map_bytes<F, B>(self, f: F) -> crate::Result<B>
+    where
+        F: FnOnce(Bytes) -> crate::Result<B>,
+    {
+        f(self.read_to_end().await?)
+    }
+
+    /// Get the full data of the field as [`Bytes`], checking that it is exactly
+    /// `expected` bytes long.
+    ///
+    /// This is useful when the field size is sent out-of-band, e.g. in a
+    /// preceding metadata field.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::UnexpectedFieldSize`] if the field data
+    /// size differs from `expected`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let bytes = field.verify_size(4).await.unwrap();
+    ///     assert_eq!(bytes, "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn verify_size(self, expected: u64) -> crate::Result<Bytes> {
+        let bytes = self.read_to_end().await?;
+        let actual = bytes.len() as u64;
+
+        if actual != expected {
+            return Err(Error::UnexpectedFieldSize { expected, actual });
+        }
+
+        Ok(bytes)
+    }
+
+    /// Drain the field data without storing it, returning the number of bytes
+    /// discarded.
+    ///
+    /// This is useful for skipping a field while still accounting for its
+    /// size, without holding the whole field data in memory as
+    /// [`read_to_end()`](Self::read_to_end) would.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let count = field.drain_and_count().await.unwrap();
+    ///     assert_eq!(count, 4);
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn drain_and_count(self) -> crate::Result<u64> {
+        let mut count = 0;
+
+        let mut this = self;
+        while let Some(bytes) = this.chunk().await? {
+            count += bytes.len() as u64;
+        }
+
+        Ok(count)
+    }
+
+    /// Discard the rest of the field data.
+    ///
+    /// The data is skipped in the internal buffer as it arrives, without
+    /// being yielded chunk by chunk as in
+    /// [`drain_and_count()`](Self::drain_and_count). Dropping the field has
+    /// the same effect, only the data is then skipped by the next
+    /// [`next_field()`](crate::Multipart::next_field) call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     field.skip().await.unwrap();
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn skip(self) -> crate::Result<()> {
+        if self.done {
+            return Ok(());
+        }
+
+        future::poll_fn(|cx| {
+            let mut lock = match self.state.try_lock() {
+                Some(lock) => lock,
+                None => return Poll::Ready(Err(Error::LockFailure)),
+            };
+
+            let state = &mut *lock;
+            state.buffer.poll_stream(cx)?;
+            Multipart::poll_skip_field_data(state, cx)
+        })
+        .await
+    }
+
+    /// Write the field data to a synchronous [`std::io::Write`] writer chunk by
+    /// chunk, returning the number of bytes written.
+    ///
+    /// The writes run on a [`tokio::task::spawn_blocking`] task so that they
+    /// don't block the executor, e.g. when writing to a file or through a
+    /// compression encoder. The writer is flushed and dropped once the field is
+    /// fully written.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tokio-io` feature to be enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::WriteFailed`] if writing to `writer`
+    /// fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let count = field.copy_to_writer(std::io::sink()).await.unwrap();
+    ///     assert_eq!(count, 4);
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "tokio-io")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tokio-io")))]
+    pub async fn copy_to_writer<W>(self, mut writer: W) -> crate::Result<u64>
+    where
+        W: std::io::Write + Send + 'static,
+    {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<Bytes>(crate::constants::COPY_TO_WRITER_QUEUED_CHUNKS);
+        let write = tokio::task::spawn_blocking(move || {
+            let mut count = 0;
+            while let Some(bytes) = rx.blocking_recv() {
+                writer.write_all(&bytes)?;
+                count += bytes.len() as u64;
+            }
+
+            writer.flush()?;
+            Ok::<_, std::io::Error>(count)
+        });
+
+        let mut this = self;
+        while let Some(bytes) = this.chunk().await? {
+            // The writer failed if the channel is closed, its error is
+            // returned below.
+            if tx.send(bytes).await.is_err() {
+                break;
+            }
+        }
+
+        drop(tx);
+        write
+            .await
+            .map_err(std::io::Error::from)
+            .and_then(|result| result)
+            .map_err(Error::WriteFailed)
+    }
+
+    /// Converts the field into an [`AsyncRead`] reader of its data, e.g. to
+    /// pass it to APIs uploading from a reader without buffering the whole
+    /// field.
+    ///
+    /// Errors are reported as [`std::io::Error`]s wrapping the [`Error`].
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tokio-io` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    /// use tokio::io::AsyncReadExt;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let mut content = String::new();
+    ///     field.into_async_read().read_to_string(&mut content).await.unwrap();
+    ///     assert_eq!(content, "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "tokio-io")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tokio-io")))]
+    pub fn into_async_read(self) -> impl AsyncRead + Unpin + Send + 'r {
+        StreamReader::new(self.map_err(std::io::Error::other))
+    }
+
+    /// Write the field data to an [`AsyncWrite`] writer chunk by chunk,
+    /// returning the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Errors reading the field are returned as is, while this method fails
+    /// with [`Error::WriteFailed`] if writing to `writer` fails.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tokio-io` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let mut buf = Vec::new();
+    ///     let count = field.write_to(&mut buf).await.unwrap();
+    ///     assert_eq!(count, 4);
+    ///     assert_eq!(buf, b"abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "tokio-io")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tokio-io")))]
+    pub async fn write_to<W: AsyncWrite + Unpin + ?Sized>(self, writer: &mut W) -> crate::Result<u64> {
+        let mut count = 0;
+
+        let mut this = self;
+        while let Some(bytes) = this.chunk().await? {
+            writer.write_all(&bytes).await.map_err(Error::WriteFailed)?;
+            count += bytes.len() as u64;
+        }
+
+        writer.flush().await.map_err(Error::WriteFailed)?;
+        Ok(count)
+    }
+
     /// Stream a chunk of the field data.
     ///
     /// When the field data has been exhausted, this will return [`None`].
     ///
+    /// # Errors
+    ///
+    /// A failure of the source stream is reported as
+    /// [`Error::FieldReadFailed`], telling the field being read and how many
+    /// bytes were received from the stream, with the
+    /// [`Error::StreamReadFailed`] as its cause.
+    ///
     /// # Examples
     ///
     /// ```
@@ -206,7 +825,47 @@ impl<'r> Field<'r> {
     #[cfg(feature = "json")]
     #[cfg_attr(nightly, doc(cfg(feature = "json")))]
     pub async fn json<T: DeserializeOwned>(self) -> crate::Result<T> {
-        serde_json::from_slice(&self.bytes().await?).map_err(Error::DecodeJson)
+        serde_json::from_slice(&self.read_to_end().await?).map_err(Error::DecodeJson)
+    }
+
+    /// Try to deserialize the field data as an untyped JSON value.
+    ///
+    /// This is the same as `field.json::<serde_json::Value>()` and is useful
+    /// for quick inspection, logging or forwarding of the field data.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::Multipart;
+    /// use bytes::Bytes;
+    /// use std::convert::Infallible;
+    /// use futures_util::stream::once;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\n{ \"name\": \"Alice\" }\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let value = field.as_json_value().await.unwrap();
+    ///     assert_eq!(value["name"], "Alice");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the field data is not in JSON format. For more
+    /// details please see [`serde_json::from_slice`].
+    #[cfg(feature = "json")]
+    #[cfg_attr(nightly, doc(cfg(feature = "json")))]
+    pub async fn as_json_value(self) -> crate::Result<serde_json::Value> {
+        self.json().await
     }
 
     /// Get the full field data as text.
@@ -232,16 +891,22 @@ impl<'r> Field<'r> {
     /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
     ///
     /// while let Some(field) = multipart.next_field().await.unwrap() {
-    ///     let content = field.text().await.unwrap();
+    ///     let content = field.read_to_string().await.unwrap();
     ///     assert_eq!(content, "abcd");
     /// }
     /// # }
     /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
     /// ```
-    pub async fn text(self) -> crate::Result<String> {
+    pub async fn read_to_string(self) -> crate::Result<String> {
         self.text_with_charset("utf-8").await
     }
 
+    /// Get the full field data as text.
+    #[deprecated(note = "renamed to `read_to_string()`")]
+    pub async fn text(self) -> crate::Result<String> {
+        self.read_to_string().await
+    }
+
     /// Get the full field data as text given a specific encoding.
     ///
     /// This method decodes the field data with `BOM sniffing` and with
@@ -274,17 +939,139 @@ impl<'r> Field<'r> {
     /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
     /// ```
     pub async fn text_with_charset(self, default_encoding: &str) -> crate::Result<String> {
-        let encoding_name = self
-            .content_type()
-            .and_then(|mime| mime.get_param(mime::CHARSET))
-            .map(|charset| charset.as_str())
-            .unwrap_or(default_encoding);
+        let encoding_name = self.charset_or_default(default_encoding);
 
         let encoding = Encoding::for_label(encoding_name.as_bytes()).unwrap_or(UTF_8);
-        let bytes = self.bytes().await?;
+        let bytes = self.read_to_end().await?;
         Ok(encoding.decode(&bytes).0.into_owned())
     }
 
+    /// Stream the field data as decoded text fragments.
+    ///
+    /// Unlike [`text_with_charset()`](Self::text_with_charset), this decodes
+    /// each chunk as it arrives, without collecting the whole field data in
+    /// memory first. Encoding is determined the same way, with `BOM sniffing`
+    /// and the `charset` parameter of `Content-Type` header prioritized over
+    /// `default_encoding`. Malformed sequences are not replaced, but yield an
+    /// [`Error::DecodeText`] error instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::{once, TryStreamExt};
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let content: String = field.text_streaming("utf-8").try_collect().await.unwrap();
+    ///     assert_eq!(content, "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn text_streaming(self, default_encoding: &str) -> impl Stream<Item = crate::Result<String>> + Send + 'r {
+        let encoding_name = self.charset_or_default(default_encoding);
+
+        let encoding = Encoding::for_label(encoding_name.as_bytes()).unwrap_or(UTF_8);
+        let decoder = encoding.new_decoder();
+
+        stream::unfold(Some((self, decoder)), |state| async move {
+            let (mut field, mut decoder) = state?;
+
+            loop {
+                let (bytes, last) = match field.chunk().await {
+                    Ok(Some(bytes)) => (bytes, false),
+                    Ok(None) => (Bytes::new(), true),
+                    Err(err) => return Some((Err(err), None)),
+                };
+
+                let text = match helpers::decode_text_chunk(&mut decoder, &bytes, last) {
+                    Some(text) => text,
+                    None => {
+                        let encoding = decoder.encoding().name().to_owned();
+                        return Some((Err(Error::DecodeText { encoding }), None));
+                    }
+                };
+
+                match (text.is_empty(), last) {
+                    (true, true) => return None,
+                    (true, false) => continue,
+                    (false, true) => return Some((Ok(text), None)),
+                    (false, false) => return Some((Ok(text), Some((field, decoder)))),
+                }
+            }
+        })
+    }
+
+    /// Stream the field data as text lines, without collecting the whole field
+    /// data in memory.
+    ///
+    /// Lines are terminated by `\n` or `\r\n`, which is not included in the
+    /// yielded lines. The text is decoded as in
+    /// [`text_streaming()`](Self::text_streaming), with UTF-8 as the default
+    /// encoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::{once, TryStreamExt};
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\nrust\r\nasync\nhttp\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     let lines: Vec<String> = field.lines().try_collect().await.unwrap();
+    ///     assert_eq!(lines, ["rust", "async", "http"]);
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn lines(self) -> impl Stream<Item = crate::Result<String>> + Send + 'r {
+        let text = Box::pin(self.text_streaming("utf-8"));
+
+        // Along with the buffered text, keep how much of it is known to hold no `\n`.
+        stream::unfold(Some((text, String::new(), 0)), |state| async move {
+            let (mut text, mut buf, mut searched) = state?;
+
+            loop {
+                if let Some(idx) = buf[searched..].find('\n') {
+                    let mut line = buf.split_off(searched + idx + 1);
+                    std::mem::swap(&mut line, &mut buf);
+
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+
+                    return Some((Ok(line), Some((text, buf, 0))));
+                }
+
+                searched = buf.len();
+                match text.next().await {
+                    Some(Ok(fragment)) => buf.push_str(&fragment),
+                    Some(Err(err)) => return Some((Err(err), None)),
+                    None if buf.is_empty() => return None,
+                    None => return Some((Ok(buf), None)),
+                }
+            }
+        })
+    }
+
     /// Get the index of this field in order they appeared in the stream.
     ///
     /// # Examples
@@ -314,6 +1101,17 @@ impl<'r> Field<'r> {
     }
 }
 
+/// Fields are compared by their metadata only: the index, the name, the file
+/// name and the content type. The field data is not compared.
+impl PartialEq for Field<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.idx == other.idx
+            && self.name() == other.name()
+            && self.file_name() == other.file_name()
+            && self.content_type() == other.content_type()
+    }
+}
+
 impl Stream for Field<'_> {
     type Item = Result<Bytes, Error>;
 
@@ -331,32 +1129,72 @@ impl Stream for Field<'_> {
 
         let state = &mut *lock;
         if let Err(err) = state.buffer.poll_stream(cx) {
+            let err = match err {
+                Error::StreamReadFailed(_) => Error::FieldReadFailed {
+                    field_name: state.curr_field_name.clone(),
+                    offset: state.buffer.stream_size_counter,
+                    cause: Box::new(err),
+                },
+                err => err,
+            };
+
             return Poll::Ready(Some(Err(err)));
         }
 
-        match state
+        let (done, bytes) = match state
             .buffer
             .read_field_data(&state.boundary, state.curr_field_name.as_deref())
         {
-            Ok(Some((done, bytes))) => {
-                state.curr_field_size_counter += bytes.len() as u64;
-
-                if state.curr_field_size_counter > state.curr_field_size_limit {
-                    return Poll::Ready(Some(Err(Error::FieldSizeExceeded {
-                        limit: state.curr_field_size_limit,
-                        field_name: state.curr_field_name.clone(),
-                    })));
-                }
+            Ok(Some(res)) => res,
+            // `poll_stream()` only returns early without reaching EOF when the
+            // underlying stream is `Pending`, which has registered our waker.
+            Ok(None) => return Poll::Pending,
+            Err(Error::IncompleteFieldData { .. }) if state.allow_missing_final_boundary => {
+                warn!("stream ended before the final boundary, ignoring it as allowed");
+                state.stage = StreamingStage::Eof;
 
-                if done {
-                    state.stage = StreamingStage::ReadingBoundary;
+                // The tail of the data is held back while looking for the boundary.
+                let bytes = state.buffer.read_full_buf();
+                if bytes.is_empty() {
                     self.done = true;
+                    return Poll::Ready(None);
                 }
 
+                (true, bytes)
+            }
+            Err(err) => return Poll::Ready(Some(Err(err))),
+        };
+
+        state.curr_field_size_counter += bytes.len() as u64;
+
+        let action = state.field_size_exceeded_action();
+        if action == Some(SizeExceededAction::Fail) {
+            return Poll::Ready(Some(Err(state.field_size_exceeded_error())));
+        }
+
+        if done && state.stage == StreamingStage::ReadingFieldData {
+            state.stage = StreamingStage::ReadingBoundary;
+        }
+
+        match action {
+            None if done => {
+                self.done = true;
                 Poll::Ready(Some(Ok(bytes)))
             }
-            Ok(None) => Poll::Pending,
-            Err(err) => Poll::Ready(Some(Err(err))),
+            None => Poll::Ready(Some(Ok(bytes))),
+            Some(action) => {
+                // Any data left is discarded by the next `next_field()`.
+                self.done = true;
+
+                // Compared as `u64` as the excess may not fit in a `usize` on 32-bit targets.
+                let excess = state.curr_field_size_counter - state.curr_field_size_limit;
+                match action {
+                    SizeExceededAction::Truncate if excess < bytes.len() as u64 => {
+                        Poll::Ready(Some(Ok(bytes.slice(..bytes.len() - excess as usize))))
+                    }
+                    _ => Poll::Ready(None),
+                }
+            }
         }
     }
 }
@@ -0,0 +1,89 @@
+use crate::Error;
+
+/// Represents rules to be applied on a single named field.
+///
+/// Please refer [`Constraints::for_named_field`](crate::Constraints::for_named_field)
+/// for more info.
+///
+/// # Examples
+///
+/// ```
+/// use multer::{Constraints, FieldConstraints};
+///
+/// let constraints = Constraints::new().for_named_field(
+///     "avatar",
+///     FieldConstraints::new()
+///         .max_size(5 * 1024 * 1024)
+///         .require_filename()
+///         .allowed_types(vec![mime::IMAGE_PNG, mime::IMAGE_JPEG]),
+/// );
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FieldConstraints {
+    pub(crate) max_size: Option<u64>,
+    pub(crate) require_filename: bool,
+    pub(crate) allowed_types: Option<Vec<mime::Mime>>,
+}
+
+impl FieldConstraints {
+    /// Creates a set of field rules with default behaviour, which does not
+    /// apply any constraint.
+    pub fn new() -> FieldConstraints {
+        FieldConstraints::default()
+    }
+
+    /// Sets size limit for the field, it overrides any
+    /// [`SizeLimit`](crate::SizeLimit) value for this field.
+    pub fn max_size(mut self, limit: u64) -> FieldConstraints {
+        self.max_size = Some(limit);
+        self
+    }
+
+    /// Requires the field to have a `filename` in its `Content-Disposition`
+    /// header.
+    pub fn require_filename(mut self) -> FieldConstraints {
+        self.require_filename = true;
+        self
+    }
+
+    /// Specify which content types should be allowed for the field. Only the
+    /// type and subtype are compared, and a `*` subtype such as
+    /// [`mime::IMAGE_STAR`] matches any subtype.
+    pub fn allowed_types(mut self, allowed_types: Vec<mime::Mime>) -> FieldConstraints {
+        self.allowed_types = Some(allowed_types);
+        self
+    }
+
+    pub(crate) fn check(
+        &self,
+        field_name: Option<&str>,
+        file_name: Option<&str>,
+        content_type: Option<&mime::Mime>,
+    ) -> crate::Result<()> {
+        if self.require_filename && file_name.is_none() {
+            return Err(Error::MissingFileName {
+                field_name: field_name.map(str::to_owned),
+            });
+        }
+
+        if let Some(ref allowed_types) = self.allowed_types {
+            let allowed = content_type
+                .map(|ct| allowed_types.iter().any(|allowed| mime_matches(allowed, ct)))
+                .unwrap_or(false);
+
+            if !allowed {
+                return Err(Error::ContentTypeNotAllowed {
+                    field_name: field_name.map(str::to_owned),
+                    content_type: content_type.map(|ct| ct.essence_str().to_owned()),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn mime_matches(allowed: &mime::Mime, content_type: &mime::Mime) -> bool {
+    allowed.type_() == content_type.type_()
+        && (allowed.subtype() == mime::STAR || allowed.subtype() == content_type.subtype())
+}
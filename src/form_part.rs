@@ -0,0 +1,119 @@
+use bytes::Bytes;
+use encoding_rs::{Encoding, UTF_8};
+use http::header::HeaderMap;
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
+
+use crate::content_disposition::ContentDisposition;
+#[cfg(feature = "json")]
+use crate::Error;
+
+/// A snapshot of a [`Field`](crate::Field), with its data fully read into
+/// memory.
+///
+/// Unlike a `Field`, it doesn't depend on the [`Multipart`](crate::Multipart)
+/// stream, so it can be kept after the stream is dropped, e.g. to process it
+/// later or store it in a queue.
+///
+/// It's created by [`field.into_form_part()`](crate::Field::into_form_part).
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use bytes::Bytes;
+/// use futures_util::stream::once;
+/// use multer::Multipart;
+///
+/// # async fn run() {
+/// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+///
+/// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+/// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+///
+/// let mut parts = Vec::new();
+/// while let Some(field) = multipart.next_field().await.unwrap() {
+///     parts.push(field.into_form_part().await.unwrap());
+/// }
+/// drop(multipart);
+///
+/// assert_eq!(parts[0].name(), Some("my_text_field"));
+/// assert_eq!(parts[0].text(), "abcd");
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FormPart {
+    pub(crate) headers: HeaderMap,
+    pub(crate) content_disposition: ContentDisposition,
+    pub(crate) content_type: Option<mime::Mime>,
+    pub(crate) idx: usize,
+    pub(crate) data: Bytes,
+}
+
+impl FormPart {
+    /// The field name found in the [`Content-Disposition`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Disposition) header.
+    pub fn name(&self) -> Option<&str> {
+        self.content_disposition.field_name()
+    }
+
+    /// The file name found in the [`Content-Disposition`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Disposition) header.
+    pub fn file_name(&self) -> Option<&str> {
+        self.content_disposition.file_name()
+    }
+
+    /// Get the content type of the field.
+    pub fn content_type(&self) -> Option<&mime::Mime> {
+        self.content_type.as_ref()
+    }
+
+    /// Get a map of headers as [`HeaderMap`].
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Get the index of this field in order they appeared in the stream.
+    pub fn index(&self) -> usize {
+        self.idx
+    }
+
+    /// Get the full data of the field as [`Bytes`].
+    pub fn bytes(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Get the full field data as text.
+    ///
+    /// The data is decoded the same way as in
+    /// [`field.read_to_string()`](crate::Field::read_to_string), with the `charset` parameter of
+    /// `Content-Type` header, defaulting to `utf-8`.
+    pub fn text(&self) -> String {
+        let encoding = self
+            .content_type
+            .as_ref()
+            .and_then(|mime| mime.get_param(mime::CHARSET))
+            .and_then(|charset| Encoding::for_label(charset.as_str().as_bytes()))
+            .unwrap_or(UTF_8);
+
+        encoding.decode(&self.data).0.into_owned()
+    }
+
+    /// Try to deserialize the field data as JSON.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `json` feature to be enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the field data is not in JSON format
+    /// or it cannot be properly deserialized to target type `T`. For more
+    /// details please see [`serde_json::from_slice`].
+    #[cfg(feature = "json")]
+    #[cfg_attr(nightly, doc(cfg(feature = "json")))]
+    pub fn json<T: DeserializeOwned>(&self) -> crate::Result<T> {
+        serde_json::from_slice(&self.data).map_err(Error::DecodeJson)
+    }
+}
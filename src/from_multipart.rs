@@ -0,0 +1,87 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use bytes::Bytes;
+
+use crate::{Field, Multipart, Result};
+
+/// A boxed future returned by the [`FromMultipart`] and [`FromField`] traits.
+pub type BoxFuture<'r, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'r>>;
+
+/// A type that can be built from a whole multipart stream.
+///
+/// This is usually derived with `#[derive(FromMultipart)]`, which fills each
+/// struct member from the field of the same name, regardless of the order in
+/// which the fields appear in the stream. Member types must implement
+/// [`FromField`], and can be wrapped in [`Option<T>`] for optional fields or
+/// [`Vec<T>`] for repeated fields, except [`Vec<u8>`] which holds the data of a
+/// single field. Fields with unknown names are ignored, and a
+/// missing required field yields [`Error::MissingField`](crate::Error::MissingField).
+///
+/// # Optional
+///
+/// This requires the optional `derive` feature to be enabled.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use bytes::Bytes;
+/// use futures_util::stream::once;
+/// use multer::{FromMultipart, Multipart};
+///
+/// #[derive(FromMultipart)]
+/// struct Profile {
+///     name: String,
+///     avatar: Option<Bytes>,
+///     tags: Vec<String>,
+/// }
+///
+/// # async fn run() {
+/// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\nrust\r\n\
+///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nAlice\r\n\
+///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"tags\"\r\n\r\nasync\r\n--X-BOUNDARY--\r\n";
+///
+/// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+/// let multipart = Multipart::new(stream, "X-BOUNDARY");
+///
+/// let profile = Profile::from_multipart(multipart).await.unwrap();
+/// assert_eq!(profile.name, "Alice");
+/// assert_eq!(profile.avatar, None);
+/// assert_eq!(profile.tags, ["rust", "async"]);
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+/// ```
+pub trait FromMultipart: Sized {
+    /// Consumes all the remaining fields of `multipart` to build `Self`.
+    fn from_multipart<'r>(multipart: Multipart<'r>) -> BoxFuture<'r, Self>;
+}
+
+/// A type that can be built from the data of a single [`Field`].
+///
+/// # Optional
+///
+/// This requires the optional `derive` feature to be enabled.
+pub trait FromField: Sized {
+    /// Consumes `field` to build `Self`.
+    fn from_field(field: Field<'_>) -> BoxFuture<'_, Self>;
+}
+
+impl FromField for String {
+    fn from_field(field: Field<'_>) -> BoxFuture<'_, Self> {
+        Box::pin(field.read_to_string())
+    }
+}
+
+impl FromField for Bytes {
+    fn from_field(field: Field<'_>) -> BoxFuture<'_, Self> {
+        Box::pin(field.read_to_end())
+    }
+}
+
+impl FromField for Vec<u8> {
+    fn from_field(field: Field<'_>) -> BoxFuture<'_, Self> {
+        Box::pin(async move { Ok(field.read_to_end().await?.to_vec()) })
+    }
+}
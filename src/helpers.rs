@@ -1,8 +1,11 @@
 use std::convert::TryFrom;
 
+use encoding_rs::{Decoder, DecoderResult};
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use httparse::Header;
 
+use crate::constants;
+
 pub(crate) fn convert_raw_headers_to_header_map(raw_headers: &[Header<'_>]) -> crate::Result<HeaderMap> {
     let mut headers = HeaderMap::with_capacity(raw_headers.len());
 
@@ -23,9 +26,263 @@ pub(crate) fn convert_raw_headers_to_header_map(raw_headers: &[Header<'_>]) -> c
     Ok(headers)
 }
 
+/// Parses the `Content-Type` header, ignoring its parameters if they are
+/// malformed like browsers do.
 pub(crate) fn parse_content_type(headers: &HeaderMap) -> Option<mime::Mime> {
-    headers
-        .get(header::CONTENT_TYPE)
-        .and_then(|val| val.to_str().ok())
-        .and_then(|val| val.parse::<mime::Mime>().ok())
+    let val = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+
+    val.parse::<mime::Mime>().ok().or_else(|| {
+        let essence = val.split(';').next().unwrap_or_default().trim();
+        essence.parse::<mime::Mime>().ok()
+    })
+}
+
+/// Decodes `src` with `decoder` without replacing malformed sequences,
+/// returning `None` if one is found.
+pub(crate) fn decode_text_chunk(decoder: &mut Decoder, mut src: &[u8], last: bool) -> Option<String> {
+    let capacity = decoder
+        .max_utf8_buffer_length_without_replacement(src.len())
+        .unwrap_or(src.len());
+    let mut text = String::with_capacity(capacity);
+
+    loop {
+        let (result, read) = decoder.decode_to_string_without_replacement(src, &mut text, last);
+        src = &src[read..];
+
+        match result {
+            DecoderResult::InputEmpty => return Some(text),
+            DecoderResult::OutputFull => text.reserve(src.len().max(4)),
+            DecoderResult::Malformed(..) => return None,
+        }
+    }
+}
+
+/// Formats a number of bytes using the largest fitting binary unit, e.g.
+/// `1536` becomes `1.5 KiB`.
+pub(crate) fn bytes_to_human(n: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+
+    let mut unit = 0;
+    let mut size = 1024;
+    while unit + 1 < UNITS.len() && n >= size * 1024 {
+        unit += 1;
+        size *= 1024;
+    }
+
+    if n.is_multiple_of(size) {
+        format!("{} {}", n / size, UNITS[unit])
+    } else {
+        format!("{:.1} {}", n as f64 / size as f64, UNITS[unit])
+    }
+}
+
+/// Parses a byte size like `1024`, `64KB` or `10 MiB`. Units are
+/// case-insensitive and all of them are binary, i.e. `1KB` is 1024 bytes.
+pub(crate) fn parse_byte_size(val: &str) -> Option<u64> {
+    let val = val.trim();
+    let digits_end = val.find(|c: char| !c.is_ascii_digit()).unwrap_or(val.len());
+    let (number, unit) = val.split_at(digits_end);
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// Checks the boundary against the [RFC 2046](https://tools.ietf.org/html/rfc2046#section-5.1.1)
+/// grammar: 1 to 70 characters from a restricted set, not ending with a
+/// space.
+pub(crate) fn is_valid_boundary(boundary: &str) -> bool {
+    let valid_char = |b: u8| b.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&b);
+
+    (1..=70).contains(&boundary.len()) && boundary.bytes().all(valid_char) && !boundary.ends_with(' ')
+}
+
+/// Detects the boundary from the first line starting with `--` within the
+/// first [`constants::MAX_BOUNDARY_DETECTION_OFFSET`] bytes of `data`.
+pub(crate) fn detect_boundary(data: &[u8]) -> Option<String> {
+    let mut line_start = 0;
+
+    while line_start <= constants::MAX_BOUNDARY_DETECTION_OFFSET {
+        let line = data.get(line_start..)?;
+        let line_end = memchr::memmem::find(line, constants::CRLF.as_bytes())?;
+
+        if line.starts_with(constants::BOUNDARY_EXT.as_bytes()) {
+            return parse_boundary_line(&line[..line_end]);
+        }
+
+        line_start += line_end + constants::CRLF.len();
+    }
+
+    None
+}
+
+/// Extracts the boundary from a boundary line without its CRLF, e.g.
+/// `--X-BOUNDARY`, ignoring any transport padding.
+pub(crate) fn parse_boundary_line(line: &[u8]) -> Option<String> {
+    let boundary = line.strip_prefix(constants::BOUNDARY_EXT.as_bytes())?;
+    let boundary = std::str::from_utf8(boundary).ok()?.trim_end_matches(&[' ', '\t'][..]);
+    Some(boundary.to_owned()).filter(|boundary| !boundary.is_empty())
+}
+
+/// Returns the extension of the last `/`-separated segment of `file_name`,
+/// following the rules of [`std::path::Path::extension()`] except that an
+/// empty extension is `None`.
+pub(crate) fn file_extension(file_name: &str) -> Option<&str> {
+    let base_name = file_name.rsplit('/').next()?;
+    match base_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => Some(ext),
+        _ => None,
+    }
+}
+
+/// Decodes data in the given `Content-Transfer-Encoding`, returning `None`
+/// if it's malformed or the encoding is not supported. Identity encodings are
+/// passed through as is.
+#[cfg(feature = "transfer-encoding")]
+pub(crate) fn decode_transfer_encoding(encoding: &str, data: bytes::Bytes) -> Option<bytes::Bytes> {
+    use base64::Engine;
+
+    match encoding.to_ascii_lowercase().as_str() {
+        "7bit" | "8bit" | "binary" => Some(data),
+        "base64" => {
+            let data: Vec<u8> = data.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .ok()
+                .map(Into::into)
+        }
+        // Decoded leniently as recommended by RFC 2045, section 6.7.
+        "quoted-printable" => quoted_printable::decode(&data[..], quoted_printable::ParseMode::Robust)
+            .ok()
+            .map(Into::into),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_content_type() {
+        fn parse(val: &'static str) -> Option<mime::Mime> {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(val));
+            parse_content_type(&headers)
+        }
+
+        assert_eq!(parse("text/plain"), Some(mime::TEXT_PLAIN));
+        assert_eq!(parse("*/*"), Some(mime::STAR_STAR));
+        assert_eq!(
+            parse("text/plain; charset=utf-8").unwrap().get_param(mime::CHARSET),
+            Some(mime::UTF_8)
+        );
+        assert_eq!(
+            parse("application/octet-stream; name=\"file.bin"),
+            Some(mime::APPLICATION_OCTET_STREAM)
+        );
+        assert_eq!(parse("text/plain; ;;"), Some(mime::TEXT_PLAIN));
+        assert_eq!(parse("text"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn test_parse_byte_size() {
+        assert_eq!(parse_byte_size("1024"), Some(1024));
+        assert_eq!(parse_byte_size(" 512 B "), Some(512));
+        assert_eq!(parse_byte_size("64KB"), Some(64 * 1024));
+        assert_eq!(parse_byte_size("10 MiB"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_byte_size("2g"), Some(2 * 1024 * 1024 * 1024));
+
+        assert_eq!(parse_byte_size(""), None);
+        assert_eq!(parse_byte_size("MB"), None);
+        assert_eq!(parse_byte_size("10 TB"), None);
+        assert_eq!(parse_byte_size("-1"), None);
+        assert_eq!(parse_byte_size("1.5MB"), None);
+        assert_eq!(parse_byte_size("99999999999999999999 GB"), None);
+    }
+
+    #[test]
+    fn test_bytes_to_human() {
+        assert_eq!(bytes_to_human(0), "0 B");
+        assert_eq!(bytes_to_human(1023), "1023 B");
+        assert_eq!(bytes_to_human(1024), "1 KiB");
+        assert_eq!(bytes_to_human(1536), "1.5 KiB");
+        assert_eq!(bytes_to_human(5 * 1024 * 1024), "5 MiB");
+        assert_eq!(bytes_to_human(3 * 1024 * 1024 * 1024), "3 GiB");
+        assert_eq!(bytes_to_human(4096 * 1024 * 1024 * 1024), "4096 GiB");
+    }
+
+    #[test]
+    fn test_is_valid_boundary() {
+        assert!(is_valid_boundary("X-BOUNDARY"));
+        assert!(is_valid_boundary("----WebKitFormBoundary7MA4YWxkTrZu0gW"));
+        assert!(is_valid_boundary("a b'()+_,-./:=?"));
+        assert!(is_valid_boundary(&"a".repeat(70)));
+
+        assert!(!is_valid_boundary(""));
+        assert!(!is_valid_boundary(&"a".repeat(71)));
+        assert!(!is_valid_boundary("trailing "));
+        assert!(!is_valid_boundary("semi;colon"));
+        assert!(!is_valid_boundary("\"quoted\""));
+    }
+
+    #[test]
+    fn test_detect_boundary() {
+        assert_eq!(detect_boundary(b"--X-BOUNDARY\r\n").as_deref(), Some("X-BOUNDARY"));
+        assert_eq!(detect_boundary(b"--X-BOUNDARY \t\r\n").as_deref(), Some("X-BOUNDARY"));
+        assert_eq!(
+            detect_boundary(b"preamble\r\n--X-BOUNDARY\r\n").as_deref(),
+            Some("X-BOUNDARY")
+        );
+
+        assert_eq!(detect_boundary(b"--X-BOUNDARY"), None);
+        assert_eq!(detect_boundary(b"--\r\n"), None);
+        assert_eq!(detect_boundary(b"no boundary\r\n"), None);
+
+        let far = format!("{}\r\n--X-BOUNDARY\r\n", "a".repeat(2048));
+        assert_eq!(detect_boundary(far.as_bytes()), None);
+    }
+
+    #[test]
+    fn test_file_extension() {
+        assert_eq!(file_extension("a-text-file.txt"), Some("txt"));
+        assert_eq!(file_extension("archive.tar.gz"), Some("gz"));
+        assert_eq!(file_extension("dir.d/file.rs"), Some("rs"));
+        assert_eq!(file_extension("C:\\dir.d\\file"), Some("d\\file"));
+        assert_eq!(file_extension("no-extension"), None);
+        assert_eq!(file_extension("dir.d/no-extension"), None);
+        assert_eq!(file_extension(".bashrc"), None);
+        assert_eq!(file_extension("trailing."), None);
+        assert_eq!(file_extension(""), None);
+    }
+
+    #[test]
+    #[cfg(feature = "transfer-encoding")]
+    fn test_decode_transfer_encoding() {
+        let decode = |encoding, data: &'static str| decode_transfer_encoding(encoding, data.into());
+
+        assert_eq!(decode("7bit", "abcd").as_deref(), Some(&b"abcd"[..]));
+        assert_eq!(decode("Binary", "abcd").as_deref(), Some(&b"abcd"[..]));
+        assert_eq!(
+            decode("base64", "SGVsbG8g\r\nd29ybGQ=").as_deref(),
+            Some(&b"Hello world"[..])
+        );
+        assert_eq!(decode("BASE64", "SGVsbG8").as_deref(), None);
+        assert_eq!(
+            decode("quoted-printable", "caf=C3=A9 au =\r\nlait").as_deref(),
+            Some("café au lait".as_bytes())
+        );
+        assert_eq!(decode("x-unknown", "abcd"), None);
+    }
 }
@@ -5,7 +5,10 @@
 //! `AsyncRead` reader as a source, so that it can be plugged into any async
 //! Rust environment e.g. any async server.
 //!
-//! To enable trace logging via the `log` crate, enable the `log` feature.
+//! To enable trace logging via the `log` crate, enable the `log` feature. To
+//! parse a whole stream into a struct with `#[derive(FromMultipart)]`, enable
+//! the `derive` feature. To parse an `http-body-util` `BoxBody` with
+//! `Multipart::from_box_body()`, enable the `http-body` feature.
 //!
 //! # Examples
 //!
@@ -94,7 +97,7 @@
 //! let mut multipart = Multipart::with_constraints(some_stream, "X-BOUNDARY", constraints);
 //!
 //! while let Some(field) = multipart.next_field().await.unwrap() {
-//!     let content = field.text().await.unwrap();
+//!     let content = field.read_to_string().await.unwrap();
 //!     assert_eq!(content, "abcd");
 //! }
 //! # }
@@ -121,11 +124,26 @@
 #![doc(test(attr(allow(unused_extern_crates, unused_variables))))]
 
 pub use bytes;
+pub use chunked_multipart::{ChunkedMultipart, SegmentAppender};
 pub use constraints::Constraints;
-pub use error::Error;
+pub use content_disposition::ContentDisposition;
+pub use error::{Error, ErrorKind};
 pub use field::Field;
-pub use multipart::Multipart;
+pub use field_constraints::FieldConstraints;
+pub use form_part::FormPart;
+#[cfg(feature = "derive")]
+#[cfg_attr(nightly, doc(cfg(feature = "derive")))]
+pub use from_multipart::{BoxFuture, FromField, FromMultipart};
+#[cfg(feature = "derive")]
+#[cfg_attr(nightly, doc(cfg(feature = "derive")))]
+pub use multer_derive::FromMultipart;
+pub use multipart::{Multipart, StreamingStage};
+pub use size_exceeded_action::SizeExceededAction;
 pub use size_limit::SizeLimit;
+pub use unknown_field_action::UnknownFieldAction;
+pub use validation_failure_action::ValidationFailureAction;
+pub use version::Version;
+pub use writer::MultipartWriter;
 
 #[cfg(feature = "log")]
 macro_rules! trace {
@@ -137,21 +155,69 @@ macro_rules! trace {
     ($($t:tt)*) => {};
 }
 
+#[cfg(feature = "log")]
+macro_rules! warn {
+    ($($t:tt)*) => (::log::warn!($($t)*););
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! warn {
+    ($($t:tt)*) => {};
+}
+
 mod buffer;
+mod chunked_multipart;
 mod constants;
 mod constraints;
 mod content_disposition;
 mod error;
 mod field;
+mod field_constraints;
+mod form_part;
+#[cfg(feature = "derive")]
+mod from_multipart;
 mod helpers;
 mod multipart;
+mod size_exceeded_action;
 mod size_limit;
+mod unknown_field_action;
+mod validation_failure_action;
+mod version;
+mod writer;
 
 /// A Result type often returned from methods that can have `multer` errors.
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// A type-erased [`Bytes`](bytes::Bytes) stream, accepted by
+/// [`Multipart::new_boxed()`].
+pub type BoxStream<'r> = std::pin::Pin<Box<dyn futures_util::stream::Stream<Item = Result<bytes::Bytes>> + Send + 'r>>;
+
+/// Boxes a [`Bytes`](bytes::Bytes) stream into a [`BoxStream`].
+///
+/// # Examples
+///
+/// ```
+/// use bytes::Bytes;
+/// use futures_util::stream::once;
+/// use multer::Multipart;
+///
+/// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+/// let stream = multer::box_stream(once(async move { Ok(Bytes::from(data)) }));
+/// let multipart = Multipart::new_boxed(stream, "X-BOUNDARY".to_owned());
+/// ```
+pub fn box_stream<'r, S>(stream: S) -> BoxStream<'r>
+where
+    S: futures_util::stream::Stream<Item = Result<bytes::Bytes>> + Send + 'r,
+{
+    Box::pin(stream)
+}
+
 /// Parses the `Content-Type` header to extract the boundary value.
 ///
+/// The MIME type must be `multipart/form-data`, this is the only place it is
+/// checked. To parse other `multipart/*` types, extract the boundary with
+/// [`extract_boundary_from_str()`] instead and pass it to [`Multipart`].
+///
 /// # Examples
 ///
 /// ```
@@ -180,6 +246,60 @@ pub fn parse_boundary<T: AsRef<str>>(content_type: T) -> Result<String> {
         .ok_or(Error::NoBoundary)
 }
 
+/// Parses the `Content-Type` header found in `headers` to extract the boundary
+/// value, as [`parse_boundary()`] does.
+///
+/// It fails with [`Error::NoMultipart`] if the header is missing or isn't
+/// `multipart/form-data`, and with [`Error::NoBoundary`] if it has no
+/// boundary.
+///
+/// # Examples
+///
+/// ```
+/// use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(CONTENT_TYPE, HeaderValue::from_static("multipart/form-data; boundary=ABCDEFG"));
+///
+/// assert_eq!(multer::parse_boundary_from_headers(&headers), Ok("ABCDEFG".to_owned()));
+/// ```
+pub fn parse_boundary_from_headers(headers: &http::HeaderMap) -> Result<String> {
+    let content_type = headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|val| val.to_str().ok())
+        .ok_or(Error::NoMultipart)?;
+
+    parse_boundary(content_type)
+}
+
+/// Extracts the boundary value from a `Content-Type` header, tolerating a
+/// missing or different MIME type.
+///
+/// It tries [`parse_boundary()`] first, then looks for a `boundary` parameter
+/// anywhere in the value. This handles values like `boundary=ABCDEFG` which
+/// some reverse proxies forward without the `multipart/form-data` prefix.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     multer::extract_boundary_from_str("multipart/form-data; boundary=ABCDEFG"),
+///     Ok("ABCDEFG".to_owned())
+/// );
+/// assert_eq!(
+///     multer::extract_boundary_from_str("boundary=ABCDEFG"),
+///     Ok("ABCDEFG".to_owned())
+/// );
+/// ```
+pub fn extract_boundary_from_str(content_type: &str) -> Result<String> {
+    parse_boundary(content_type).or_else(|err| {
+        constants::extract_param(content_type.as_bytes(), b"boundary")
+            .filter(|boundary| !boundary.is_empty())
+            .map(|boundary| boundary.into_owned())
+            .ok_or(err)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -201,4 +321,57 @@ mod tests {
         let content_type = "text/plain; boundary=------ABCDEFG";
         assert!(parse_boundary(content_type).is_err());
     }
+
+    #[test]
+    fn test_parse_boundary_from_headers() {
+        use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_boundary_from_headers(&headers), Err(Error::NoMultipart));
+
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=ABCDEFG"),
+        );
+        assert_eq!(parse_boundary_from_headers(&headers), Ok("ABCDEFG".to_owned()));
+
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("multipart/form-data"));
+        assert_eq!(parse_boundary_from_headers(&headers), Err(Error::NoBoundary));
+
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/plain; boundary=ABCDEFG"));
+        assert_eq!(parse_boundary_from_headers(&headers), Err(Error::NoMultipart));
+
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_bytes(b"multipart/form-data; boundary=\xff").unwrap(),
+        );
+        assert_eq!(parse_boundary_from_headers(&headers), Err(Error::NoMultipart));
+    }
+
+    #[test]
+    fn test_extract_boundary_from_str() {
+        let content_type = "multipart/form-data; boundary=ABCDEFG";
+        assert_eq!(extract_boundary_from_str(content_type), Ok("ABCDEFG".to_owned()));
+
+        let content_type = "multipart/form-data; boundary=\"AB CD\"";
+        assert_eq!(extract_boundary_from_str(content_type), Ok("AB CD".to_owned()));
+
+        let content_type = "boundary=------ABCDEFG";
+        assert_eq!(extract_boundary_from_str(content_type), Ok("------ABCDEFG".to_owned()));
+
+        let content_type = "boundary = ABCDEFG";
+        assert_eq!(extract_boundary_from_str(content_type), Ok("ABCDEFG".to_owned()));
+
+        let content_type = "; boundary=\"ABCDEFG\"";
+        assert_eq!(extract_boundary_from_str(content_type), Ok("ABCDEFG".to_owned()));
+
+        let content_type = "multipart/form-data";
+        assert_eq!(extract_boundary_from_str(content_type), Err(Error::NoBoundary));
+
+        let content_type = "boundary=";
+        assert!(extract_boundary_from_str(content_type).is_err());
+
+        let content_type = "myboundary=ABCDEFG";
+        assert!(extract_boundary_from_str(content_type).is_err());
+    }
 }
@@ -1,25 +1,51 @@
+use std::collections::HashMap;
+use std::convert::{Infallible, TryFrom};
+use std::fmt;
+use std::future::Future;
+#[cfg(feature = "tokio-io")]
+use std::path::Path;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use bytes::Bytes;
+use bytes::{Buf, Bytes, BytesMut};
 use futures_util::future;
-use futures_util::stream::{Stream, TryStreamExt};
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+#[cfg(feature = "http-body")]
+use http_body_util::{combinators::BoxBody, BodyExt};
+#[cfg(feature = "indexmap")]
+use indexmap::IndexMap;
 use spin::mutex::spin::SpinMutex as Mutex;
+#[cfg(feature = "tempfile")]
+use {tempfile::NamedTempFile, tokio::io::AsyncWriteExt};
 #[cfg(feature = "tokio-io")]
 use {tokio::io::AsyncRead, tokio_util::io::ReaderStream};
 
 use crate::buffer::StreamBuffer;
-use crate::constraints::Constraints;
+use crate::constraints::{Constraints, SizeExceededCallback};
 use crate::content_disposition::ContentDisposition;
 use crate::error::Error;
 use crate::field::Field;
-use crate::{constants, helpers, Result};
+use crate::size_exceeded_action::SizeExceededAction;
+use crate::size_limit::SizeLimit;
+use crate::unknown_field_action::UnknownFieldAction;
+use crate::validation_failure_action::ValidationFailureAction;
+use crate::{constants, helpers, BoxStream, Result};
 
 /// Represents the implementation of `multipart/form-data` formatted data.
 ///
 /// This will parse the source stream into [`Field`] instances via
 /// [`next_field()`](Self::next_field).
 ///
+/// # Other Multipart Types
+///
+/// `Multipart` only looks at the boundaries and doesn't check the MIME type of
+/// the stream, which is up to [`parse_boundary()`](crate::parse_boundary). As
+/// the boundary syntax is the same for every `multipart/*` type, it also parses
+/// e.g. `multipart/mixed`, `multipart/related` or `multipart/alternative`
+/// bodies, whose parts have no name unless they come with a
+/// `Content-Disposition` header.
+///
 /// # Field Exclusivity
 ///
 /// A `Field` represents a raw, self-decoding stream into multipart data. As
@@ -66,7 +92,7 @@ use crate::{constants, helpers, Result};
 /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
 ///
 /// while let Some(field) = multipart.next_field().await.unwrap() {
-///     println!("Field: {:?}", field.text().await)
+///     println!("Field: {:?}", field.read_to_string().await)
 /// }
 /// # }
 /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
@@ -74,9 +100,11 @@ use crate::{constants, helpers, Result};
 #[derive(Debug)]
 pub struct Multipart<'r> {
     state: Arc<Mutex<MultipartState<'r>>>,
+    constraints: Constraints,
+    last_error: Option<Error>,
+    stream_terminated: bool,
 }
 
-#[derive(Debug)]
 pub(crate) struct MultipartState<'r> {
     pub(crate) buffer: StreamBuffer<'r>,
     pub(crate) boundary: String,
@@ -85,17 +113,81 @@ pub(crate) struct MultipartState<'r> {
     pub(crate) curr_field_name: Option<String>,
     pub(crate) curr_field_size_limit: u64,
     pub(crate) curr_field_size_counter: u64,
-    pub(crate) constraints: Constraints,
+    pub(crate) curr_field_size_exceeded: bool,
+    pub(crate) field_name_counts: HashMap<String, usize>,
+    pub(crate) allow_missing_final_boundary: bool,
+    pub(crate) on_field_size_exceeded: Option<Arc<SizeExceededCallback>>,
+}
+
+impl MultipartState<'_> {
+    /// Returns what to do with the current field if it exceeded its size
+    /// limit, asking the `on_field_size_exceeded` handler the first time.
+    pub(crate) fn field_size_exceeded_action(&mut self) -> Option<SizeExceededAction> {
+        if self.curr_field_size_exceeded {
+            // The handler already chose to discard the rest of the field.
+            return Some(SizeExceededAction::Skip);
+        }
+
+        if self.curr_field_size_counter <= self.curr_field_size_limit {
+            return None;
+        }
+
+        let action = match &self.on_field_size_exceeded {
+            Some(handler) => handler(
+                self.curr_field_name.as_deref().unwrap_or(""),
+                self.curr_field_size_limit,
+            ),
+            None => SizeExceededAction::Fail,
+        };
+
+        self.curr_field_size_exceeded = action != SizeExceededAction::Fail;
+        Some(action)
+    }
+
+    pub(crate) fn field_size_exceeded_error(&self) -> Error {
+        Error::FieldSizeExceeded {
+            limit: self.curr_field_size_limit,
+            field_name: self.curr_field_name.clone(),
+        }
+    }
 }
 
+impl fmt::Debug for MultipartState<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartState")
+            .field("buffer", &self.buffer)
+            .field("boundary", &self.boundary)
+            .field("stage", &self.stage)
+            .field("next_field_idx", &self.next_field_idx)
+            .field("curr_field_name", &self.curr_field_name)
+            .field("curr_field_size_limit", &self.curr_field_size_limit)
+            .field("curr_field_size_counter", &self.curr_field_size_counter)
+            .field("curr_field_size_exceeded", &self.curr_field_size_exceeded)
+            .field("field_name_counts", &self.field_name_counts)
+            .field("allow_missing_final_boundary", &self.allow_missing_final_boundary)
+            .field("on_field_size_exceeded", &self.on_field_size_exceeded.is_some())
+            .finish()
+    }
+}
+
+/// The stage of the multipart stream parsing, reported by
+/// [`Error::IncompleteStream`].
+#[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum StreamingStage {
+pub enum StreamingStage {
+    /// Skipping the preamble to find the first boundary.
     FindingFirstBoundary,
+    /// Reading a boundary.
     ReadingBoundary,
+    /// Checking whether a boundary is the closing one.
     DeterminingBoundaryType,
+    /// Skipping the whitespace following a boundary.
     ReadingTransportPadding,
+    /// Reading the headers of a field.
     ReadingFieldHeaders,
+    /// Reading the data of a field.
     ReadingFieldData,
+    /// The closing boundary was read.
     Eof,
 }
 
@@ -115,6 +207,114 @@ impl<'r> Multipart<'r> {
     /// Construct a new `Multipart` instance with the given [`Bytes`] stream and
     /// the boundary.
     pub fn with_constraints<S, O, E, B>(stream: S, boundary: B, constraints: Constraints) -> Self
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'r,
+        O: Into<Bytes> + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'r,
+        B: Into<String>,
+    {
+        Multipart::with_constraints_and_capacity(stream, boundary, constraints, 0)
+    }
+
+    /// Construct a new `Multipart` instance with the given [`Bytes`] stream,
+    /// the boundary and the length of the stream, e.g. from the
+    /// `Content-Length` header.
+    ///
+    /// The length is used to pre-allocate the internal buffer, avoiding
+    /// reallocations as the data comes in. The pre-allocation is capped at
+    /// 1 MiB, so a bogus length can't make it allocate a huge buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new_with_length_hint(stream, "X-BOUNDARY", data.len() as u64);
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn new_with_length_hint<S, O, E, B>(stream: S, boundary: B, content_length: u64) -> Self
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'r,
+        O: Into<Bytes> + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'r,
+        B: Into<String>,
+    {
+        let initial_capacity = usize::try_from(content_length).unwrap_or(usize::MAX);
+        Multipart::with_constraints_and_capacity(stream, boundary, Constraints::default(), initial_capacity)
+    }
+
+    /// Construct a new `Multipart` instance with the given [`Bytes`] stream,
+    /// the boundary and the size limits for the whole stream and for each
+    /// field.
+    ///
+    /// This is a shortcut for [`with_constraints()`](Self::with_constraints)
+    /// with a [`SizeLimit`] setting
+    /// [`whole_stream`](SizeLimit::whole_stream) and
+    /// [`per_field`](SizeLimit::per_field).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new_with_limits(stream, "X-BOUNDARY", 15 * 1024 * 1024, 1024 * 1024);
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn new_with_limits<S, O, E, B>(stream: S, boundary: B, whole_stream: u64, per_field: u64) -> Self
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'r,
+        O: Into<Bytes> + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'r,
+        B: Into<String>,
+    {
+        let size_limit = SizeLimit::new().whole_stream(whole_stream).per_field(per_field);
+        Multipart::with_constraints(stream, boundary, Constraints::new().size_limit(size_limit))
+    }
+
+    /// Construct a new `Multipart` instance with the given type-erased
+    /// [`BoxStream`] and the boundary.
+    ///
+    /// Unlike the generic [`new()`](Self::new), this isn't instantiated for
+    /// every stream type, which is useful for code that already boxes its
+    /// streams. See [`box_stream()`](crate::box_stream) to box a stream.
+    pub fn new_boxed(stream: BoxStream<'r>, boundary: String) -> Self {
+        Multipart::from_boxed_stream(stream, boundary, Constraints::default(), 0)
+    }
+
+    fn with_constraints_and_capacity<S, O, E, B>(
+        stream: S,
+        boundary: B,
+        constraints: Constraints,
+        initial_capacity: usize,
+    ) -> Self
     where
         S: Stream<Item = Result<O, E>> + Send + 'r,
         O: Into<Bytes> + 'static,
@@ -125,20 +325,157 @@ impl<'r> Multipart<'r> {
             .map_ok(|b| b.into())
             .map_err(|err| Error::StreamReadFailed(err.into()));
 
+        Multipart::from_boxed_stream(Box::pin(stream), boundary.into(), constraints, initial_capacity)
+    }
+
+    fn from_boxed_stream(
+        stream: BoxStream<'r>,
+        boundary: String,
+        constraints: Constraints,
+        initial_capacity: usize,
+    ) -> Self {
         Multipart {
             state: Arc::new(Mutex::new(MultipartState {
-                buffer: StreamBuffer::new(stream, constraints.size_limit.whole_stream),
-                boundary: boundary.into(),
+                buffer: StreamBuffer::new(
+                    stream,
+                    constraints.size_limit.whole_stream,
+                    initial_capacity,
+                    constraints.on_chunk_received.clone(),
+                ),
+                boundary,
                 stage: StreamingStage::FindingFirstBoundary,
                 next_field_idx: 0,
                 curr_field_name: None,
                 curr_field_size_limit: constraints.size_limit.per_field,
                 curr_field_size_counter: 0,
-                constraints,
+                curr_field_size_exceeded: false,
+                field_name_counts: HashMap::new(),
+                allow_missing_final_boundary: constraints.allow_missing_final_boundary,
+                on_field_size_exceeded: constraints.on_field_size_exceeded.clone(),
             })),
+            constraints,
+            last_error: None,
+            stream_terminated: false,
         }
     }
 
+    /// Construct a new `Multipart` instance from a complete multipart body,
+    /// detecting the boundary from the data itself.
+    ///
+    /// The boundary is taken from the first line starting with `--` found
+    /// within the first 1 KiB of `data`, e.g. for replaying a saved request
+    /// body. This is a heuristic; prefer [`Multipart::new`] with the boundary
+    /// from the `Content-Type` header whenever it is available.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::NoBoundary`] if no boundary line is
+    /// found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = Bytes::from(
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n",
+    /// );
+    /// let mut multipart = Multipart::from_raw_bytes_with_auto_detect_boundary(data).unwrap();
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn from_raw_bytes_with_auto_detect_boundary(data: Bytes) -> Result<Self> {
+        let boundary = helpers::detect_boundary(&data).ok_or(Error::NoBoundary)?;
+        let stream = stream::once(future::ready(Ok::<_, Infallible>(data)));
+        Ok(Multipart::new(stream, boundary))
+    }
+
+    /// Construct a new `Multipart` instance with the given [`Bytes`] stream,
+    /// detecting the boundary from the stream itself.
+    ///
+    /// The stream must start with the first boundary line, e.g.
+    /// `--X-BOUNDARY\r\n`, as a raw body saved to disk does. Only the first
+    /// 256 bytes are buffered to find it, they are then parsed as usual.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::NoBoundary`] if the stream doesn't
+    /// start with a boundary line, or with [`Error::StreamReadFailed`] if
+    /// reading the stream fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::from_stream_with_auto_boundary(stream).await.unwrap();
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn from_stream_with_auto_boundary<S, O, E>(stream: S) -> Result<Self>
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'r,
+        O: Into<Bytes> + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'r,
+    {
+        let mut stream: BoxStream<'r> = Box::pin(
+            stream
+                .map_ok(|b| b.into())
+                .map_err(|err| Error::StreamReadFailed(err.into())),
+        );
+
+        let mut buf = BytesMut::new();
+        let boundary = loop {
+            let head = &buf[..buf.len().min(constants::MAX_BOUNDARY_LINE_LEN)];
+
+            // Fail early on a stream not starting with `--`.
+            let ext_len = head.len().min(constants::BOUNDARY_EXT.len());
+            if head[..ext_len] != constants::BOUNDARY_EXT.as_bytes()[..ext_len] {
+                return Err(Error::NoBoundary);
+            }
+
+            if let Some(idx) = memchr::memmem::find(head, constants::CRLF.as_bytes()) {
+                break helpers::parse_boundary_line(&head[..idx]).ok_or(Error::NoBoundary)?;
+            }
+
+            if head.len() == constants::MAX_BOUNDARY_LINE_LEN {
+                return Err(Error::NoBoundary);
+            }
+
+            match stream.try_next().await? {
+                Some(chunk) => buf.extend_from_slice(&chunk),
+                None => return Err(Error::NoBoundary),
+            }
+        };
+
+        let stream = stream::once(future::ready(Ok(buf.freeze()))).chain(stream);
+        Ok(Multipart::from_boxed_stream(
+            Box::pin(stream),
+            boundary,
+            Constraints::default(),
+            0,
+        ))
+    }
+
     /// Construct a new `Multipart` instance with the given [`AsyncRead`] reader
     /// and the boundary.
     ///
@@ -213,222 +550,369 @@ impl<'r> Multipart<'r> {
         Multipart::with_constraints(stream, boundary, constraints)
     }
 
-    /// Yields the next [`Field`] if available.
+    /// Construct a new `Multipart` instance with the given [`AsyncRead`] reader
+    /// and the boundary, reading the data in chunks of up to `chunk_size`
+    /// bytes.
     ///
-    /// Any previous `Field` returned by this method must be dropped before
-    /// calling this method or [`Multipart::next_field_with_idx()`] again. See
-    /// [field-exclusivity](#field-exclusivity) for details.
-    pub async fn next_field(&mut self) -> Result<Option<Field<'r>>> {
-        future::poll_fn(|cx| self.poll_next_field(cx)).await
+    /// The [`with_reader()`](Self::with_reader) method reads 4 KiB at a time.
+    /// A larger chunk size reduces the number of polls for large uploads:
+    /// typically 8 KiB is good for interactive use and 64 KiB for batch
+    /// uploads.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tokio-io` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let reader = data.as_bytes();
+    /// let mut multipart = Multipart::with_reader_and_chunk_size(reader, "X-BOUNDARY", 64 * 1024);
+    ///
+    /// while let Some(mut field) = multipart.next_field().await.unwrap() {
+    ///     while let Some(chunk) = field.chunk().await.unwrap() {
+    ///         println!("Chunk: {:?}", chunk);
+    ///     }
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "tokio-io")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tokio-io")))]
+    pub fn with_reader_and_chunk_size<R, B>(reader: R, boundary: B, chunk_size: usize) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'r,
+        B: Into<String>,
+    {
+        Multipart::with_reader_with_constraints_and_chunk_size(reader, boundary, Constraints::default(), chunk_size)
     }
 
-    /// Yields the next [`Field`] if available.
+    /// Construct a new `Multipart` instance with the given [`AsyncRead`] reader,
+    /// the boundary and the constraints, reading the data in chunks of up to
+    /// `chunk_size` bytes.
     ///
-    /// Any previous `Field` returned by this method must be dropped before
-    /// calling this method or [`Multipart::next_field_with_idx()`] again. See
-    /// [field-exclusivity](#field-exclusivity) for details.
+    /// See [`with_reader_and_chunk_size()`](Self::with_reader_and_chunk_size)
+    /// for choosing a chunk size.
     ///
-    /// This method is available since version 2.1.0.
-    pub fn poll_next_field(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Field<'r>>>> {
-        // This is consistent as we have an `&mut` and `Field` is not `Clone`.
-        // Here, we are guaranteeing that the returned `Field` will be the
-        // _only_ field with access to the multipart parsing state. This ensure
-        // that lock failure can never occur. This is effectively a dynamic
-        // version of passing an `&mut` of `self` to the `Field`.
-        if Arc::strong_count(&self.state) != 1 {
-            return Poll::Ready(Err(Error::LockFailure));
-        }
+    /// # Optional
+    ///
+    /// This requires the optional `tokio-io` feature to be enabled.
+    #[cfg(feature = "tokio-io")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tokio-io")))]
+    pub fn with_reader_with_constraints_and_chunk_size<R, B>(
+        reader: R,
+        boundary: B,
+        constraints: Constraints,
+        chunk_size: usize,
+    ) -> Self
+    where
+        R: AsyncRead + Unpin + Send + 'r,
+        B: Into<String>,
+    {
+        let stream = ReaderStream::with_capacity(reader, chunk_size);
+        Multipart::with_constraints(stream, boundary, constraints)
+    }
 
-        debug_assert_eq!(Arc::strong_count(&self.state), 1);
-        debug_assert!(self.state.try_lock().is_some(), "expected exlusive lock");
-        let mut lock = match self.state.try_lock() {
-            Some(lock) => lock,
-            None => return Poll::Ready(Err(Error::LockFailure)),
-        };
+    /// Construct a new `Multipart` instance reading from the process's standard
+    /// input and the boundary.
+    ///
+    /// This is useful for command-line tools processing a multipart body piped
+    /// from another program.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tokio-io` feature to be enabled.
+    #[cfg(feature = "tokio-io")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tokio-io")))]
+    pub fn new_from_stdin<B>(boundary: B) -> Self
+    where
+        B: Into<String>,
+    {
+        Multipart::with_reader(tokio::io::stdin(), boundary)
+    }
 
-        let state = &mut *lock;
-        if state.stage == StreamingStage::Eof {
-            return Poll::Ready(Ok(None));
-        }
+    /// Construct a new `Multipart` instance reading from the file at `path` and
+    /// the boundary.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tokio-io` feature to be enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::StreamReadFailed`] if the file cannot be
+    /// opened.
+    #[cfg(feature = "tokio-io")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tokio-io")))]
+    pub async fn from_file<P, B>(path: P, boundary: B) -> Result<Self>
+    where
+        P: AsRef<Path>,
+        B: Into<String>,
+    {
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(|err| Error::StreamReadFailed(err.into()))?;
 
-        state.buffer.poll_stream(cx)?;
+        Ok(Multipart::with_reader(file, boundary))
+    }
 
-        if state.stage == StreamingStage::FindingFirstBoundary {
-            let boundary = &state.boundary;
-            let boundary_deriv = format!("{}{}", constants::BOUNDARY_EXT, boundary);
-            match state.buffer.read_to(boundary_deriv.as_bytes()) {
-                Some(_) => state.stage = StreamingStage::ReadingBoundary,
-                None => {
-                    state.buffer.poll_stream(cx)?;
-                    if state.buffer.eof {
-                        return Poll::Ready(Err(Error::IncompleteStream));
-                    }
-                }
-            }
-        }
-
-        // The previous field did not finish reading its data.
-        if state.stage == StreamingStage::ReadingFieldData {
-            match state
-                .buffer
-                .read_field_data(state.boundary.as_str(), state.curr_field_name.as_deref())?
-            {
-                Some((done, bytes)) => {
-                    state.curr_field_size_counter += bytes.len() as u64;
-
-                    if state.curr_field_size_counter > state.curr_field_size_limit {
-                        return Poll::Ready(Err(Error::FieldSizeExceeded {
-                            limit: state.curr_field_size_limit,
-                            field_name: state.curr_field_name.clone(),
-                        }));
+    /// Construct a new `Multipart` instance with the given synchronous
+    /// [`std::io::Read`] reader and the boundary.
+    ///
+    /// Each read is run on the blocking thread pool of the runtime `rt`, so
+    /// that it doesn't block the async tasks. The data is read in chunks of
+    /// up to 64 KiB to limit the number of blocking tasks.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tokio-io` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    ///
+    /// use multer::Multipart;
+    /// use tokio::runtime::Handle;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let reader = Cursor::new(data);
+    /// let mut multipart = Multipart::from_sync_reader_with_runtime(reader, "X-BOUNDARY", &Handle::current());
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "tokio-io")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tokio-io")))]
+    pub fn from_sync_reader_with_runtime<R, B>(reader: R, boundary: B, rt: &tokio::runtime::Handle) -> Self
+    where
+        R: std::io::Read + Send + 'static,
+        B: Into<String>,
+    {
+        let rt = rt.clone();
+        let stream = stream::try_unfold(reader, move |mut reader| {
+            let read = rt.spawn_blocking(move || {
+                let mut buf = vec![0; constants::SYNC_READER_CHUNK_SIZE];
+                let result = loop {
+                    match reader.read(&mut buf) {
+                        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                        result => break result,
                     }
+                };
 
-                    if done {
-                        state.stage = StreamingStage::ReadingBoundary;
-                    } else {
-                        return Poll::Pending;
-                    }
-                }
-                None => {
-                    return Poll::Pending;
+                result.map(|len| {
+                    buf.truncate(len);
+                    (Bytes::from(buf), reader)
+                })
+            });
+
+            async move {
+                match read.await.map_err(std::io::Error::from)?? {
+                    (bytes, _) if bytes.is_empty() => Ok::<_, std::io::Error>(None),
+                    chunk => Ok(Some(chunk)),
                 }
             }
-        }
-
-        if state.stage == StreamingStage::ReadingBoundary {
-            let boundary = &state.boundary;
-            let boundary_deriv_len = constants::BOUNDARY_EXT.len() + boundary.len();
+        });
 
-            let boundary_bytes = match state.buffer.read_exact(boundary_deriv_len) {
-                Some(bytes) => bytes,
-                None => {
-                    return if state.buffer.eof {
-                        Poll::Ready(Err(Error::IncompleteStream))
-                    } else {
-                        Poll::Pending
-                    };
-                }
-            };
+        Multipart::new(stream, boundary)
+    }
 
-            if &boundary_bytes[..] == format!("{}{}", constants::BOUNDARY_EXT, boundary).as_bytes() {
-                state.stage = StreamingStage::DeterminingBoundaryType;
-            } else {
-                return Poll::Ready(Err(Error::IncompleteStream));
-            }
-        }
+    /// Construct a new `Multipart` instance with the given [`BoxBody`] and the
+    /// boundary.
+    ///
+    /// Only the data frames of the body are parsed, trailers are ignored.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `http-body` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use http_body_util::combinators::BoxBody;
+    /// use http_body_util::{BodyExt, Full};
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data =
+    ///     "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let body: BoxBody<Bytes, std::io::Error> = Full::new(Bytes::from(data)).map_err(|err| match err {}).boxed();
+    /// let mut multipart = Multipart::from_box_body(body, "X-BOUNDARY");
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "http-body")]
+    #[cfg_attr(nightly, doc(cfg(feature = "http-body")))]
+    pub fn from_box_body<E, B>(body: BoxBody<Bytes, E>, boundary: B) -> Self
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+        B: Into<String>,
+    {
+        Multipart::new(body.into_data_stream(), boundary)
+    }
 
-        if state.stage == StreamingStage::DeterminingBoundaryType {
-            let ext_len = constants::BOUNDARY_EXT.len();
-            let next_bytes = match state.buffer.peek_exact(ext_len) {
-                Some(bytes) => bytes,
-                None => {
-                    return if state.buffer.eof {
-                        Poll::Ready(Err(Error::IncompleteStream))
-                    } else {
-                        Poll::Pending
-                    };
-                }
-            };
+    /// Yields the next [`Field`] if available.
+    ///
+    /// Any previous `Field` returned by this method must be dropped before
+    /// calling this method or [`Multipart::next_field_with_idx()`] again. See
+    /// [field-exclusivity](#field-exclusivity) for details.
+    pub async fn next_field(&mut self) -> Result<Option<Field<'r>>> {
+        future::poll_fn(|cx| self.poll_next_field(cx)).await
+    }
 
-            if next_bytes == constants::BOUNDARY_EXT.as_bytes() {
-                state.stage = StreamingStage::Eof;
-                return Poll::Ready(Ok(None));
-            } else {
-                state.stage = StreamingStage::ReadingTransportPadding;
+    /// Yields the next [`Field`] having a name, along with its name.
+    ///
+    /// Fields without a `name` parameter in their `Content-Disposition` header
+    /// are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data\r\n\r\nunnamed\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some((name, field)) = multipart.try_next_named().await.unwrap() {
+    ///     assert_eq!(name, "my_text_field");
+    ///     assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn try_next_named(&mut self) -> Result<Option<(String, Field<'r>)>> {
+        while let Some(field) = self.next_field().await? {
+            if let Some(name) = field.name() {
+                return Ok(Some((name.to_owned(), field)));
             }
         }
 
-        if state.stage == StreamingStage::ReadingTransportPadding {
-            if !state.buffer.advance_past_transport_padding() {
-                return if state.buffer.eof {
-                    Poll::Ready(Err(Error::IncompleteStream))
-                } else {
-                    Poll::Pending
-                };
-            }
-
-            let crlf_len = constants::CRLF.len();
-            let crlf_bytes = match state.buffer.read_exact(crlf_len) {
-                Some(bytes) => bytes,
-                None => {
-                    return if state.buffer.eof {
-                        Poll::Ready(Err(Error::IncompleteStream))
-                    } else {
-                        Poll::Pending
-                    };
-                }
-            };
+        Ok(None)
+    }
 
-            if &crlf_bytes[..] == constants::CRLF.as_bytes() {
-                state.stage = StreamingStage::ReadingFieldHeaders;
-            } else {
-                return Poll::Ready(Err(Error::IncompleteStream));
+    /// Yields the next [`Field`] with the given name, skipping the fields
+    /// before it.
+    ///
+    /// The data of the skipped fields is discarded without being read into
+    /// memory. Returns [`None`] if the stream ends before such a field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; \
+    ///     filename=\"a.txt\"\r\n\r\nHello\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let field = multipart.next_field_named("my_file_field").await.unwrap().unwrap();
+    /// assert_eq!(field.read_to_string().await.unwrap(), "Hello");
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn next_field_named(&mut self, name: &str) -> Result<Option<Field<'r>>> {
+        while let Some(field) = self.next_field().await? {
+            if field.name() == Some(name) {
+                return Ok(Some(field));
             }
         }
 
-        if state.stage == StreamingStage::ReadingFieldHeaders {
-            let header_bytes = match state.buffer.read_until(constants::CRLF_CRLF.as_bytes()) {
-                Some(bytes) => bytes,
-                None => {
-                    return if state.buffer.eof {
-                        return Poll::Ready(Err(Error::IncompleteStream));
-                    } else {
-                        Poll::Pending
-                    };
-                }
-            };
-
-            let mut headers = [httparse::EMPTY_HEADER; constants::MAX_HEADERS];
-
-            let headers = match httparse::parse_headers(&header_bytes, &mut headers).map_err(Error::ReadHeaderFailed)? {
-                httparse::Status::Complete((_, raw_headers)) => {
-                    match helpers::convert_raw_headers_to_header_map(raw_headers) {
-                        Ok(headers) => headers,
-                        Err(err) => {
-                            return Poll::Ready(Err(err));
-                        }
-                    }
-                }
-                httparse::Status::Partial => {
-                    return Poll::Ready(Err(Error::IncompleteHeaders));
-                }
-            };
-
-            state.stage = StreamingStage::ReadingFieldData;
-
-            let field_idx = state.next_field_idx;
-            state.next_field_idx += 1;
-
-            let content_disposition = ContentDisposition::parse(&headers);
-            let field_size_limit = state
-                .constraints
-                .size_limit
-                .extract_size_limit_for(content_disposition.field_name.as_deref());
+        Ok(None)
+    }
 
-            state.curr_field_name = content_disposition.field_name.clone();
-            state.curr_field_size_limit = field_size_limit;
-            state.curr_field_size_counter = 0;
+    /// Yields the next [`Field`] if available, continuing past the fields
+    /// rejected by the [`Constraints`].
+    ///
+    /// Unlike [`next_field()`](Self::next_field), a rejected field, e.g. an
+    /// unknown one, yields `Some(Err(_))` and the following call moves on to
+    /// the next field. Errors the parsing can't recover from, like a stream
+    /// failure or [`Error::LockFailure`] when the previous field is still
+    /// alive, end the iteration with `None` and are available from
+    /// [`last_error()`](Self::last_error).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::{Constraints, Multipart};
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b\"\r\n\r\n2\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let constraints = Constraints::new().allowed_fields(vec!["b"]);
+    /// let mut multipart = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
+    ///
+    /// assert!(multipart.next_field_result().await.unwrap().is_err());
+    ///
+    /// let field = multipart.next_field_result().await.unwrap().unwrap();
+    /// assert_eq!(field.read_to_string().await.unwrap(), "2");
+    ///
+    /// assert!(multipart.next_field_result().await.is_none());
+    /// assert!(multipart.last_error().is_none());
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn next_field_result(&mut self) -> Option<Result<Field<'r>>> {
+        if self.last_error.is_some() {
+            return None;
+        }
 
-            let field_name = content_disposition.field_name.as_deref();
-            if !state.constraints.is_it_allowed(field_name) {
-                return Poll::Ready(Err(Error::UnknownField {
-                    field_name: field_name.map(str::to_owned),
-                }));
+        match self.next_field().await {
+            Ok(field) => field.map(Ok),
+            Err(err) if err.is_recoverable() => Some(Err(err)),
+            Err(err) => {
+                self.last_error = Some(err);
+                None
             }
-
-            drop(lock); // The lock will be dropped anyway, but let's be explicit.
-            let field = Field::new(self.state.clone(), headers, field_idx, content_disposition);
-            return Poll::Ready(Ok(Some(field)));
         }
+    }
 
-        Poll::Pending
+    /// Get the error which ended the iteration with
+    /// [`next_field_result()`](Self::next_field_result), if any.
+    pub fn last_error(&self) -> Option<&Error> {
+        self.last_error.as_ref()
     }
 
-    /// Yields the next [`Field`] with their positioning index as a tuple
-    /// `(`[`usize`]`, `[`Field`]`)`.
+    /// Checks whether any field remains in the stream, without yielding it.
     ///
-    /// Any previous `Field` returned by this method must be dropped before
-    /// calling this method or [`Multipart::next_field()`] again. See
+    /// This reads from the stream until the next boundary is found, skipping
+    /// the data of the previous field if it was not read. The next field, if
+    /// any, is left for [`next_field()`](Self::next_field).
+    ///
+    /// Any previous `Field` must be dropped before calling this method. See
     /// [field-exclusivity](#field-exclusivity) for details.
     ///
     /// # Examples
@@ -443,17 +927,1207 @@ impl<'r> Multipart<'r> {
     /// # async fn run() {
     /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
     ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
-    ///
     /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
     /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
     ///
-    /// while let Some((idx, field)) = multipart.next_field_with_idx().await.unwrap() {
-    ///     println!("Index: {:?}, Content: {:?}", idx, field.text().await)
-    /// }
+    /// assert!(multipart.has_remaining_fields().await.unwrap());
+    /// assert!(multipart.has_remaining_fields().await.unwrap());
+    ///
+    /// let field = multipart.next_field().await.unwrap().unwrap();
+    /// assert_eq!(field.index(), 0);
+    /// drop(field);
+    ///
+    /// assert!(!multipart.has_remaining_fields().await.unwrap());
     /// # }
     /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
     /// ```
-    pub async fn next_field_with_idx(&mut self) -> Result<Option<(usize, Field<'r>)>> {
-        self.next_field().await.map(|f| f.map(|field| (field.index(), field)))
+    pub async fn has_remaining_fields(&mut self) -> Result<bool> {
+        future::poll_fn(|cx| self.poll_has_remaining_fields(cx)).await
+    }
+
+    fn poll_has_remaining_fields(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        if Arc::strong_count(&self.state) != 1 {
+            return Poll::Ready(Err(Error::LockFailure));
+        }
+
+        let mut lock = match self.state.try_lock() {
+            Some(lock) => lock,
+            None => return Poll::Ready(Err(Error::LockFailure)),
+        };
+
+        let state = &mut *lock;
+        if state.stage == StreamingStage::Eof {
+            return Poll::Ready(Ok(false));
+        }
+
+        state.buffer.poll_stream(cx)?;
+
+        if Multipart::poll_skip_field_data(state, cx)?.is_pending() {
+            return Poll::Pending;
+        }
+
+        let delimiter = format!("{}{}", constants::BOUNDARY_EXT, state.boundary);
+        let offset = match state.stage {
+            StreamingStage::FindingFirstBoundary => {
+                let preamble_limit = self.constraints.size_limit.preamble;
+                match state.buffer.read_to_with_limit(delimiter.as_bytes(), preamble_limit)? {
+                    Some(_) => state.stage = StreamingStage::ReadingBoundary,
+                    None if state.buffer.eof => {
+                        return Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }));
+                    }
+                    None => return Poll::Pending,
+                }
+                delimiter.len()
+            }
+            StreamingStage::ReadingBoundary => delimiter.len(),
+            StreamingStage::DeterminingBoundaryType => 0,
+            StreamingStage::Eof => return Poll::Ready(Ok(false)),
+            // A boundary starting a new field has already been read.
+            _ => return Poll::Ready(Ok(true)),
+        };
+
+        let ext_len = constants::BOUNDARY_EXT.len();
+        match state.buffer.peek_exact(offset + ext_len) {
+            Some(bytes) if offset > 0 && &bytes[..offset] != delimiter.as_bytes() => {
+                Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }))
+            }
+            Some(bytes) => Poll::Ready(Ok(&bytes[offset..] != constants::BOUNDARY_EXT.as_bytes())),
+            None if state.buffer.eof => Poll::Ready(Err(Error::IncompleteStream { stage: state.stage })),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Skips the data of the previous field if it was not read, polling the
+    /// stream in between so that we only return `Pending` once the stream
+    /// itself is `Pending` and has registered our waker.
+    pub(crate) fn poll_skip_field_data(state: &mut MultipartState<'r>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        while state.stage == StreamingStage::ReadingFieldData {
+            let (found, skipped) = match state
+                .buffer
+                .advance_to_boundary(state.boundary.as_str(), state.curr_field_name.as_deref())
+            {
+                Err(Error::IncompleteFieldData { .. }) if state.allow_missing_final_boundary => {
+                    warn!("stream ended before the final boundary, ignoring it as allowed");
+                    state.stage = StreamingStage::Eof;
+                    return Poll::Ready(Ok(()));
+                }
+                res => res?,
+            };
+
+            state.curr_field_size_counter += skipped as u64;
+
+            if let Some(SizeExceededAction::Fail) = state.field_size_exceeded_action() {
+                return Poll::Ready(Err(state.field_size_exceeded_error()));
+            }
+
+            if found {
+                state.stage = StreamingStage::ReadingBoundary;
+            } else {
+                let buf_len = state.buffer.buf.len();
+                state.buffer.poll_stream(cx)?;
+
+                if state.buffer.buf.len() == buf_len && !state.buffer.eof {
+                    return Poll::Pending;
+                }
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Yields the next [`Field`] if available.
+    ///
+    /// Any previous `Field` returned by this method must be dropped before
+    /// calling this method or [`Multipart::next_field_with_idx()`] again. See
+    /// [field-exclusivity](#field-exclusivity) for details.
+    ///
+    /// This is the poll-based counterpart of [`next_field()`](Self::next_field)
+    /// for driving the parser manually, e.g. from a hand-written [`Future`] or
+    /// [`Stream`] implementation. When `Poll::Pending` is returned, the waker in
+    /// `cx` has been registered with the underlying stream and the current task
+    /// will be woken once more data is available.
+    ///
+    /// This method is available since version 2.1.0.
+    ///
+    /// [`Future`]: std::future::Future
+    pub fn poll_next_field(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<Field<'r>>>> {
+        loop {
+            let (field, discard) = match self.poll_next_unvalidated_field(cx) {
+                Poll::Ready(Ok(Some(res))) => res,
+                Poll::Ready(Ok(None)) => return Poll::Ready(Ok(None)),
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            if discard {
+                trace!("discarding field: {:?}", field.name());
+                // The field data is drained by the next iteration.
+                drop(field);
+                continue;
+            }
+
+            match &self.constraints.field_validator {
+                Some(validator) if !validator(&field) => match self.constraints.validation_failure_action {
+                    ValidationFailureAction::Fail => {
+                        return Poll::Ready(Err(Error::FieldValidationFailed {
+                            field_name: field.name().map(str::to_owned),
+                        }));
+                    }
+                    // The field data is drained by the next iteration.
+                    ValidationFailureAction::Skip => drop(field),
+                },
+                _ => return Poll::Ready(Ok(Some(field))),
+            }
+        }
+    }
+
+    /// Yields the next [`Field`] checked against the constraints, except the
+    /// validator, along with whether it is to be discarded.
+    fn poll_next_unvalidated_field(&mut self, cx: &mut Context<'_>) -> Poll<Result<Option<(Field<'r>, bool)>>> {
+        // This is consistent as we have an `&mut` and `Field` is not `Clone`.
+        // Here, we are guaranteeing that the returned `Field` will be the
+        // _only_ field with access to the multipart parsing state. This ensure
+        // that lock failure can never occur. This is effectively a dynamic
+        // version of passing an `&mut` of `self` to the `Field`.
+        if Arc::strong_count(&self.state) != 1 {
+            return Poll::Ready(Err(Error::LockFailure));
+        }
+
+        debug_assert_eq!(Arc::strong_count(&self.state), 1);
+        debug_assert!(self.state.try_lock().is_some(), "expected exlusive lock");
+        let mut lock = match self.state.try_lock() {
+            Some(lock) => lock,
+            None => return Poll::Ready(Err(Error::LockFailure)),
+        };
+
+        let state = &mut *lock;
+
+        if let Some(expected) = &self.constraints.expected_boundary {
+            if *expected != state.boundary {
+                return Poll::Ready(Err(Error::BoundaryMismatch {
+                    expected: expected.clone(),
+                    actual: state.boundary.clone(),
+                }));
+            }
+        }
+
+        if state.stage == StreamingStage::Eof {
+            return Poll::Ready(Ok(None));
+        }
+
+        // Pick up any changes made through `constraints_mut()`.
+        state.buffer.whole_stream_size_limit = self.constraints.size_limit.whole_stream;
+        state.buffer.on_chunk_received = self.constraints.on_chunk_received.clone();
+        state.allow_missing_final_boundary = self.constraints.allow_missing_final_boundary;
+        state.on_field_size_exceeded = self.constraints.on_field_size_exceeded.clone();
+
+        state.buffer.poll_stream(cx)?;
+
+        if state.stage == StreamingStage::FindingFirstBoundary {
+            let boundary = &state.boundary;
+            let boundary_deriv = format!("{}{}", constants::BOUNDARY_EXT, boundary);
+            let preamble_limit = self.constraints.size_limit.preamble;
+            match state
+                .buffer
+                .read_to_with_limit(boundary_deriv.as_bytes(), preamble_limit)?
+            {
+                Some(_) => state.stage = StreamingStage::ReadingBoundary,
+                None => {
+                    state.buffer.poll_stream(cx)?;
+                    if state.buffer.eof {
+                        return Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }));
+                    }
+                }
+            }
+        }
+
+        if Multipart::poll_skip_field_data(state, cx)?.is_pending() {
+            return Poll::Pending;
+        }
+
+        if state.stage == StreamingStage::Eof {
+            return Poll::Ready(Ok(None));
+        }
+
+        if state.stage == StreamingStage::ReadingBoundary {
+            let boundary = &state.boundary;
+            let boundary_deriv_len = constants::BOUNDARY_EXT.len() + boundary.len();
+
+            let boundary_bytes = match state.buffer.read_exact(boundary_deriv_len) {
+                Some(bytes) => bytes,
+                None => {
+                    return if state.buffer.eof {
+                        Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }))
+                    } else {
+                        Poll::Pending
+                    };
+                }
+            };
+
+            if &boundary_bytes[..] == format!("{}{}", constants::BOUNDARY_EXT, boundary).as_bytes() {
+                state.stage = StreamingStage::DeterminingBoundaryType;
+            } else {
+                return Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }));
+            }
+        }
+
+        if state.stage == StreamingStage::DeterminingBoundaryType {
+            let ext_len = constants::BOUNDARY_EXT.len();
+            let next_bytes = match state.buffer.peek_exact(ext_len) {
+                Some(bytes) => bytes,
+                None => {
+                    return if state.buffer.eof {
+                        Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }))
+                    } else {
+                        Poll::Pending
+                    };
+                }
+            };
+
+            if next_bytes == constants::BOUNDARY_EXT.as_bytes() {
+                state.stage = StreamingStage::Eof;
+                return Poll::Ready(Ok(None));
+            } else {
+                state.stage = StreamingStage::ReadingTransportPadding;
+            }
+        }
+
+        if state.stage == StreamingStage::ReadingTransportPadding {
+            if !state.buffer.advance_past_transport_padding() {
+                return if state.buffer.eof {
+                    Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }))
+                } else {
+                    Poll::Pending
+                };
+            }
+
+            let crlf_len = constants::CRLF.len();
+            let crlf_bytes = match state.buffer.read_exact(crlf_len) {
+                Some(bytes) => bytes,
+                None => {
+                    return if state.buffer.eof {
+                        Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }))
+                    } else {
+                        Poll::Pending
+                    };
+                }
+            };
+
+            if &crlf_bytes[..] == constants::CRLF.as_bytes() {
+                state.stage = StreamingStage::ReadingFieldHeaders;
+            } else {
+                return Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }));
+            }
+        }
+
+        if state.stage == StreamingStage::ReadingFieldHeaders {
+            if let Some(max) = self.constraints.max_fields {
+                if state.next_field_idx >= max {
+                    return Poll::Ready(Err(Error::FieldCountExceeded { limit: max }));
+                }
+            }
+
+            let headers_limit = self.constraints.size_limit.headers;
+
+            // Parse the headers in place, they are only consumed once parsed.
+            let header_bytes = match state.buffer.try_peek_until(constants::CRLF_CRLF.as_bytes()) {
+                Some(bytes) if bytes.len() as u64 > headers_limit => {
+                    return Poll::Ready(Err(Error::HeaderSizeExceeded { limit: headers_limit }));
+                }
+                Some(bytes) => bytes,
+                // The headers can't end any earlier than this once more data arrives.
+                None if state.buffer.buf.len() as u64 >= headers_limit => {
+                    return Poll::Ready(Err(Error::HeaderSizeExceeded { limit: headers_limit }));
+                }
+                None => {
+                    return if state.buffer.eof {
+                        return Poll::Ready(Err(Error::IncompleteStream { stage: state.stage }));
+                    } else {
+                        Poll::Pending
+                    };
+                }
+            };
+            let header_len = header_bytes.len();
+
+            let mut headers = [httparse::EMPTY_HEADER; constants::MAX_HEADERS];
+
+            let headers = match httparse::parse_headers(header_bytes, &mut headers).map_err(Error::ReadHeaderFailed)? {
+                httparse::Status::Complete((_, raw_headers)) => {
+                    match helpers::convert_raw_headers_to_header_map(raw_headers) {
+                        Ok(headers) => headers,
+                        Err(err) => {
+                            return Poll::Ready(Err(err));
+                        }
+                    }
+                }
+                httparse::Status::Partial => {
+                    return Poll::Ready(Err(Error::IncompleteHeaders));
+                }
+            };
+
+            state.buffer.buf.advance(header_len);
+
+            state.stage = StreamingStage::ReadingFieldData;
+
+            let field_idx = state.next_field_idx;
+            state.next_field_idx += 1;
+
+            let content_disposition = ContentDisposition::parse(&headers);
+            let field_size_limit = self.constraints.size_limit_for(
+                content_disposition.field_name.as_deref(),
+                content_disposition.file_name.is_some(),
+            );
+
+            state.curr_field_name = content_disposition.field_name.clone();
+            state.curr_field_size_limit = field_size_limit;
+            state.curr_field_size_counter = 0;
+            state.curr_field_size_exceeded = false;
+
+            let field_name = content_disposition.field_name.as_deref();
+            if let (Some(name), Some(max)) = (field_name, self.constraints.max_field_name_length) {
+                if name.len() > max {
+                    return Poll::Ready(Err(Error::FieldNameTooLong {
+                        limit: max,
+                        name: name.to_owned(),
+                    }));
+                }
+            }
+
+            if let (Some(file_name), Some(max)) = (
+                content_disposition.file_name.as_deref(),
+                self.constraints.max_filename_length,
+            ) {
+                if file_name.len() > max {
+                    return Poll::Ready(Err(Error::FileNameTooLong {
+                        limit: max,
+                        name: file_name.to_owned(),
+                    }));
+                }
+            }
+
+            if self.constraints.is_ignored(field_name) {
+                // Discarded by `poll_next_field()` without checking the rules.
+                drop(lock);
+                let field = Field::new(self.state.clone(), headers, field_idx, content_disposition);
+                return Poll::Ready(Ok(Some((field, true))));
+            }
+
+            if !self.constraints.is_it_allowed(field_name) {
+                match self.constraints.unknown_field_action_for(field_name) {
+                    UnknownFieldAction::Skip => {
+                        drop(lock);
+                        let field = Field::new(self.state.clone(), headers, field_idx, content_disposition);
+                        return Poll::Ready(Ok(Some((field, true))));
+                    }
+                    UnknownFieldAction::Warn => {
+                        warn!("accepting unknown field: {:?}", field_name);
+                    }
+                    UnknownFieldAction::Fail => {
+                        return Poll::Ready(Err(Error::UnknownField {
+                            field_name: field_name.map(str::to_owned),
+                        }));
+                    }
+                }
+            }
+
+            if let (Some(name), Some(max)) = (field_name, self.constraints.max_fields_for(field_name)) {
+                let count = state.field_name_counts.entry(name.to_owned()).or_insert(0);
+                *count += 1;
+
+                if *count > max {
+                    return Poll::Ready(Err(Error::DuplicateFieldLimitExceeded {
+                        limit: max,
+                        field_name: name.to_owned(),
+                    }));
+                }
+            }
+
+            if let Some(field_constraints) = self.constraints.field_constraints_for(field_name) {
+                let content_type = helpers::parse_content_type(&headers);
+                let file_name = content_disposition.file_name.as_deref();
+                if let Err(err) = field_constraints.check(field_name, file_name, content_type.as_ref()) {
+                    return Poll::Ready(Err(err));
+                }
+            }
+
+            drop(lock); // The lock will be dropped anyway, but let's be explicit.
+            let field = Field::new(self.state.clone(), headers, field_idx, content_disposition);
+            return Poll::Ready(Ok(Some((field, false))));
+        }
+
+        Poll::Pending
+    }
+
+    /// Yields the next [`Field`] with their positioning index as a tuple
+    /// `(`[`usize`]`, `[`Field`]`)`.
+    ///
+    /// Any previous `Field` returned by this method must be dropped before
+    /// calling this method or [`Multipart::next_field()`] again. See
+    /// [field-exclusivity](#field-exclusivity) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// while let Some((idx, field)) = multipart.next_field_with_idx().await.unwrap() {
+    ///     println!("Index: {:?}, Content: {:?}", idx, field.read_to_string().await)
+    /// }
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn next_field_with_idx(&mut self) -> Result<Option<(usize, Field<'r>)>> {
+        self.next_field().await.map(|f| f.map(|field| (field.index(), field)))
+    }
+
+    /// Yields the [`Field`] at position `idx`, discarding any fields before it.
+    ///
+    /// Returns [`None`] if the stream ends before reaching `idx`.
+    ///
+    /// Any previous `Field` returned by this `Multipart` must be dropped
+    /// before calling this method. See [field-exclusivity](#field-exclusivity)
+    /// for details.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::FieldAlreadyConsumed`] if the field at
+    /// `idx` has already been yielded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"first\"\r\n\r\nabcd\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"second\"\r\n\r\nefgh\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let field = multipart.skip_to_field(1).await.unwrap().unwrap();
+    /// assert_eq!(field.name(), Some("second"));
+    /// drop(field);
+    ///
+    /// assert!(multipart.skip_to_field(0).await.is_err());
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn skip_to_field(&mut self, idx: usize) -> Result<Option<Field<'r>>> {
+        let current_index = self.state.lock().next_field_idx;
+        if idx < current_index {
+            return Err(Error::FieldAlreadyConsumed {
+                requested_index: idx,
+                current_index,
+            });
+        }
+
+        while let Some(field) = self.next_field().await? {
+            if field.index() == idx {
+                return Ok(Some(field));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Calls `f` on every remaining [`Field`] in order, consuming the
+    /// `Multipart`.
+    ///
+    /// Each field is processed to completion before the next one is read. The
+    /// first error returned by `f` or by the parser stops the iteration and is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// multipart
+    ///     .try_for_each(|field| async move {
+    ///         println!("Field: {:?}", field.read_to_string().await?);
+    ///         Ok(())
+    ///     })
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn try_for_each<F, Fut>(mut self, f: F) -> Result<()>
+    where
+        F: FnMut(Field<'r>) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        self.try_for_each_ref(f).await
+    }
+
+    /// Calls `f` on every remaining [`Field`] in order, without consuming the
+    /// `Multipart`.
+    ///
+    /// See [`try_for_each()`](Self::try_for_each) for details.
+    pub async fn try_for_each_ref<F, Fut>(&mut self, mut f: F) -> Result<()>
+    where
+        F: FnMut(Field<'r>) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        while let Some(field) = self.next_field().await? {
+            f(field).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads every remaining [`Field`] into memory, consuming the `Multipart`,
+    /// and returns their names and data in the order they appear.
+    ///
+    /// Mind the [size limits](crate::SizeLimit) when the fields are
+    /// untrusted, as all of them are buffered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b\"\r\n\r\n1\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n2\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let fields = multipart.collect_ordered().await.unwrap();
+    /// assert_eq!(fields, [(Some("b".to_owned()), Bytes::from("1")), (Some("a".to_owned()), Bytes::from("2"))]);
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn collect_ordered(mut self) -> Result<Vec<(Option<String>, Bytes)>> {
+        let mut fields = Vec::new();
+        while let Some(field) = self.next_field().await? {
+            let name = field.name().map(|name| name.to_owned());
+            fields.push((name, field.read_to_end().await?));
+        }
+
+        Ok(fields)
+    }
+
+    /// Reads every remaining named [`Field`] into memory, consuming the
+    /// `Multipart`, and groups their data by name in the order the names first
+    /// appear. Fields without a name are skipped.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `indexmap` feature to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b\"\r\n\r\n1\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n2\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b\"\r\n\r\n3\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let fields = multipart.collect_to_index_map().await.unwrap();
+    /// assert_eq!(fields.keys().collect::<Vec<_>>(), ["b", "a"]);
+    /// assert_eq!(fields["b"], [Bytes::from("1"), Bytes::from("3")]);
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "indexmap")]
+    #[cfg_attr(nightly, doc(cfg(feature = "indexmap")))]
+    pub async fn collect_to_index_map(mut self) -> Result<IndexMap<String, Vec<Bytes>>> {
+        let mut fields = IndexMap::<String, Vec<Bytes>>::new();
+        while let Some(field) = self.next_field().await? {
+            if let Some(name) = field.name().map(|name| name.to_owned()) {
+                let data = field.read_to_end().await?;
+                fields.entry(name).or_default().push(data);
+            }
+        }
+
+        Ok(fields)
+    }
+
+    /// Reads every remaining named text [`Field`] as a string, consuming the
+    /// `Multipart`, and collects them by name. Fields with a file name, and
+    /// fields without a name, are skipped.
+    ///
+    /// If several fields have the same name, the last one wins; see
+    /// [`collect_text_fields_multi()`](Self::collect_text_fields_multi) to keep
+    /// all of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nAlice\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"a.png\"\r\n\r\n...\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nBob\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let fields = multipart.collect_text_fields().await.unwrap();
+    /// assert_eq!(fields.len(), 1);
+    /// assert_eq!(fields["name"], "Bob");
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn collect_text_fields(mut self) -> Result<HashMap<String, String>> {
+        let mut fields = HashMap::new();
+        while let Some(field) = self.next_field().await? {
+            if field.file_name().is_some() {
+                continue;
+            }
+
+            if let Some(name) = field.name().map(|name| name.to_owned()) {
+                fields.insert(name, field.read_to_string().await?);
+            }
+        }
+
+        Ok(fields)
+    }
+
+    /// Reads every remaining named text [`Field`] as a string, consuming the
+    /// `Multipart`, and groups them by name, e.g. for the selected options of
+    /// a `<select multiple>`. Fields with a file name, and fields without a
+    /// name, are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"color\"\r\n\r\nred\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"color\"\r\n\r\nblue\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let fields = multipart.collect_text_fields_multi().await.unwrap();
+    /// assert_eq!(fields["color"], ["red", "blue"]);
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn collect_text_fields_multi(mut self) -> Result<HashMap<String, Vec<String>>> {
+        let mut fields = HashMap::<String, Vec<String>>::new();
+        while let Some(field) = self.next_field().await? {
+            if field.file_name().is_some() {
+                continue;
+            }
+
+            if let Some(name) = field.name().map(|name| name.to_owned()) {
+                let text = field.read_to_string().await?;
+                fields.entry(name).or_default().push(text);
+            }
+        }
+
+        Ok(fields)
+    }
+
+    /// Maps every remaining [`Field`] to a stream with `f` and flattens the
+    /// results into a single stream, consuming the `Multipart`.
+    ///
+    /// Each field's stream is drained before the next field is read. Parser
+    /// errors and errors yielded by the field streams are forwarded as they
+    /// occur.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::{once, TryStreamExt};
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// // Stream the chunks of all the fields one after another.
+    /// let chunks: Vec<Bytes> = multipart.flat_map_fields(|field| field).try_collect().await.unwrap();
+    /// assert_eq!(chunks.concat(), b"abcd");
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn flat_map_fields<F, S, B>(self, f: F) -> impl Stream<Item = Result<B>> + 'r
+    where
+        F: FnMut(Field<'r>) -> S + 'r,
+        S: Stream<Item = Result<B>> + 'r,
+        B: 'r,
+    {
+        let fields = stream::try_unfold((self, f), |(mut this, mut f)| async move {
+            let field = this.next_field().await?;
+            Ok(field.map(|field| (f(field), (this, f))))
+        });
+
+        fields.try_flatten()
+    }
+
+    /// Skips to the field named `name` and writes its data to a new temporary
+    /// file in the system temporary directory.
+    ///
+    /// Any fields preceding the named field are discarded. The returned
+    /// [`NamedTempFile`] is deleted when dropped unless it is persisted.
+    ///
+    /// # Optional
+    ///
+    /// This requires the optional `tempfile` feature to be enabled.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::MissingField`] if no field named `name`
+    /// is left in the stream, and with [`Error::WriteFailed`] if the temporary
+    /// file cannot be created or written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; \
+    ///     filename=\"a.txt\"\r\n\r\nHello\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let file = multipart.read_field_to_tempfile("my_file_field").await.unwrap();
+    /// assert_eq!(std::fs::read(file.path()).unwrap(), b"Hello");
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(feature = "tempfile")]
+    #[cfg_attr(nightly, doc(cfg(feature = "tempfile")))]
+    pub async fn read_field_to_tempfile(&mut self, name: &str) -> Result<NamedTempFile> {
+        if let Some(mut field) = self.next_field_named(name).await? {
+            // Creating and opening the file are blocking calls.
+            let (file, std_file) = tokio::task::spawn_blocking(|| {
+                let file = NamedTempFile::new()?;
+                let std_file = file.reopen()?;
+                Ok::<_, std::io::Error>((file, std_file))
+            })
+            .await
+            .map_err(std::io::Error::from)
+            .and_then(|result| result)
+            .map_err(Error::WriteFailed)?;
+
+            let mut writer = tokio::fs::File::from_std(std_file);
+            while let Some(chunk) = field.chunk().await? {
+                writer.write_all(&chunk).await.map_err(Error::WriteFailed)?;
+            }
+
+            writer.flush().await.map_err(Error::WriteFailed)?;
+            return Ok(file);
+        }
+
+        Err(Error::MissingField {
+            field_name: name.to_owned(),
+        })
+    }
+
+    /// Returns the total number of bytes read from the underlying stream so
+    /// far.
+    ///
+    /// This counts every byte pulled from the source stream, including
+    /// boundaries and headers, and is the same value checked against the
+    /// [`whole_stream`](crate::SizeLimit::whole_stream) size limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    /// assert_eq!(multipart.stream_size_consumed(), 0);
+    ///
+    /// while let Some(field) = multipart.next_field().await.unwrap() {
+    ///     field.read_to_end().await.unwrap();
+    /// }
+    ///
+    /// assert_eq!(multipart.stream_size_consumed(), data.len() as u64);
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn stream_size_consumed(&self) -> u64 {
+        self.state.lock().buffer.stream_size_counter
+    }
+
+    /// Reads the rest of the stream without parsing it, consuming the
+    /// `Multipart`, and returns the total number of bytes of the stream.
+    ///
+    /// The count includes the bytes already read, as
+    /// [`stream_size_consumed()`](Self::stream_size_consumed) does, and the
+    /// remaining fields with their headers and boundaries. The remaining data
+    /// is discarded as it arrives, without being buffered.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::LockFailure`] if a [`Field`] is still
+    /// alive, or with [`Error::StreamSizeExceeded`] if the stream exceeds the
+    /// [`whole_stream`](crate::SizeLimit::whole_stream) size limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// assert_eq!(multipart.drain_and_count().await.unwrap(), data.len() as u64);
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub async fn drain_and_count(self) -> Result<u64> {
+        if Arc::strong_count(&self.state) != 1 {
+            return Err(Error::LockFailure);
+        }
+
+        future::poll_fn(|cx| {
+            let mut state = self.state.try_lock().ok_or(Error::LockFailure)?;
+            state.stage = StreamingStage::Eof;
+            state
+                .buffer
+                .poll_drain(cx)
+                .map_ok(|()| state.buffer.stream_size_counter)
+        })
+        .await
+    }
+
+    /// Returns the configured size limit for the whole stream.
+    ///
+    /// Together with [`stream_size_consumed()`](Self::stream_size_consumed),
+    /// this can be used to compute the remaining byte budget.
+    pub fn whole_stream_limit(&self) -> u64 {
+        self.constraints.size_limit.whole_stream
+    }
+
+    /// Get the constraints applied to the stream.
+    pub fn constraints(&self) -> &Constraints {
+        &self.constraints
+    }
+
+    /// Get a mutable reference to the constraints applied to the stream, e.g.
+    /// to adjust the limits for the authenticated user.
+    ///
+    /// Changes take effect from the next call to
+    /// [`next_field()`](Self::next_field). Note that a field already being
+    /// read keeps the size limit it started with, see
+    /// [`set_current_field_size_limit()`](Self::set_current_field_size_limit)
+    /// to change it, and the data read so far still counts towards the new
+    /// limit of the whole stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::{Constraints, Multipart, SizeLimit};
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// *multipart.constraints_mut() = Constraints::new().size_limit(SizeLimit::new().whole_stream(16));
+    ///
+    /// assert!(multipart.next_field().await.is_err());
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn constraints_mut(&mut self) -> &mut Constraints {
+        &mut self.constraints
+    }
+
+    /// Sets the size limit of the field currently being read, i.e. the last
+    /// one yielded by [`next_field()`](Self::next_field), overriding the one
+    /// from the [`Constraints`].
+    ///
+    /// It can be called while the [`Field`] is alive, even after part of its
+    /// data was read, which still counts towards the new limit.
+    ///
+    /// # Errors
+    ///
+    /// This method fails with [`Error::FieldSizeExceeded`] if more data than
+    /// `limit` was already read, in which case the limit is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::{Constraints, Multipart, SizeLimit};
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_file_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let constraints = Constraints::new().size_limit(SizeLimit::new().per_field(2));
+    /// let mut multipart = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
+    ///
+    /// let field = multipart.next_field().await.unwrap().unwrap();
+    /// // e.g. when uploaded by an admin.
+    /// multipart.set_current_field_size_limit(1024).unwrap();
+    ///
+    /// assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn set_current_field_size_limit(&mut self, limit: u64) -> Result<()> {
+        let mut state = self.state.lock();
+
+        if state.curr_field_size_counter > limit {
+            return Err(Error::FieldSizeExceeded {
+                limit,
+                field_name: state.curr_field_name.clone(),
+            });
+        }
+
+        state.curr_field_size_limit = limit;
+        Ok(())
+    }
+
+    /// Counts the fields remaining in the stream without consuming them.
+    ///
+    /// This only works once the whole source stream has been read into the
+    /// internal buffer, e.g. after the first [`next_field()`](Self::next_field)
+    /// call on a stream yielding the complete body in one chunk. Otherwise it
+    /// returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b\"\r\n\r\n2\r\n\
+    ///     --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"c\"\r\n\r\n3\r\n--X-BOUNDARY--\r\n";
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// // Nothing has been read from the stream yet.
+    /// assert_eq!(multipart.estimate_field_count(), None);
+    ///
+    /// let field = multipart.next_field().await.unwrap().unwrap();
+    /// assert_eq!(field.name(), Some("a"));
+    /// assert_eq!(multipart.estimate_field_count(), Some(2));
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    pub fn estimate_field_count(&self) -> Option<usize> {
+        let state = self.state.lock();
+        if !state.buffer.eof {
+            return None;
+        }
+
+        let buf = &state.buffer.buf[..];
+        let delimiter = format!("{}{}", constants::BOUNDARY_EXT, state.boundary);
+
+        let count = memchr::memmem::find_iter(buf, delimiter.as_bytes())
+            // A boundary must start at the beginning of a line.
+            .filter(|&idx| idx == 0 || buf[..idx].ends_with(constants::CRLF.as_bytes()))
+            // The closing boundary doesn't start a new field.
+            .filter(|&idx| !buf[idx + delimiter.len()..].starts_with(constants::BOUNDARY_EXT.as_bytes()))
+            .count();
+
+        Some(count)
+    }
+
+    /// Returns a multi-line summary of the current parser state, useful to
+    /// include in bug reports about parse failures.
+    ///
+    /// The buffered data is shown as hex, up to its first 64 bytes. This is
+    /// only available in debug builds, so the example isn't run as a doctest.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use std::convert::Infallible;
+    ///
+    /// use bytes::Bytes;
+    /// use futures_util::stream::once;
+    /// use multer::Multipart;
+    ///
+    /// # async fn run() {
+    /// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+    ///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    ///
+    /// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+    /// let mut multipart = Multipart::new(stream, "X-BOUNDARY");
+    ///
+    /// let field = multipart.next_field().await.unwrap().unwrap();
+    /// drop(field);
+    ///
+    /// let dump = multipart.debug_dump();
+    /// assert!(dump.contains("stage: ReadingFieldData"));
+    /// assert!(dump.contains("next_field_idx: 1"));
+    /// # }
+    /// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+    /// ```
+    #[cfg(debug_assertions)]
+    pub fn debug_dump(&self) -> String {
+        use std::fmt::Write;
+
+        let state = self.state.lock();
+        let head = state
+            .buffer
+            .buf
+            .iter()
+            .take(64)
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut dump = String::new();
+        let _ = writeln!(dump, "stage: {:?}", state.stage);
+        let _ = writeln!(dump, "boundary: {:?}", state.boundary);
+        let _ = writeln!(dump, "next_field_idx: {}", state.next_field_idx);
+        let _ = writeln!(dump, "curr_field_name: {:?}", state.curr_field_name);
+        let _ = writeln!(dump, "stream_size_counter: {}", state.buffer.stream_size_counter);
+        let _ = writeln!(dump, "buf_len: {}", state.buffer.buf.len());
+        let _ = writeln!(dump, "buf_head: [{}]", head);
+        let _ = writeln!(dump, "eof: {}", state.buffer.eof);
+        dump
+    }
+}
+
+/// Yields the fields as [`next_field()`](Multipart::next_field) does, ending
+/// the stream when there are no more fields.
+///
+/// Like with [`next_field_result()`](Multipart::next_field_result), errors
+/// rejecting a single field are yielded and the stream moves on to the next
+/// field, while an error the parsing can't recover from, e.g. a stream
+/// failure, is yielded once and ends the stream.
+///
+/// Each `Field` must still be dropped before the next one is polled, see
+/// [field-exclusivity](Multipart#field-exclusivity), otherwise the stream
+/// yields [`Error::LockFailure`] and ends. Avoid combinators holding on to
+/// several fields like `collect()` and consume every field before asking for
+/// the next one, e.g. with `and_then()`.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use bytes::Bytes;
+/// use futures_util::stream::{once, TryStreamExt};
+/// use multer::Multipart;
+///
+/// # async fn run() {
+/// let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; \
+///     name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+///
+/// let stream = once(async move { Result::<Bytes, Infallible>::Ok(Bytes::from(data)) });
+/// let multipart = Multipart::new(stream, "X-BOUNDARY");
+///
+/// let contents: Vec<String> = multipart.and_then(|field| field.read_to_string()).try_collect().await.unwrap();
+/// assert_eq!(contents, ["abcd"]);
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+/// ```
+impl<'r> Stream for Multipart<'r> {
+    type Item = Result<Field<'r>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.stream_terminated || this.last_error.is_some() {
+            return Poll::Ready(None);
+        }
+
+        match this.poll_next_field(cx) {
+            Poll::Ready(Err(err)) if !err.is_recoverable() => {
+                this.stream_terminated = true;
+                Poll::Ready(Some(Err(err)))
+            }
+            poll => poll.map(Result::transpose),
+        }
     }
 }
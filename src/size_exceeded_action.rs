@@ -0,0 +1,17 @@
+/// What to do with a field exceeding its size limit, as decided by the
+/// handler set with
+/// [`Constraints::on_field_size_exceeded()`](crate::Constraints::on_field_size_exceeded).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeExceededAction {
+    /// Fail with [`Error::FieldSizeExceeded`](crate::Error::FieldSizeExceeded),
+    /// the behaviour without a handler.
+    #[default]
+    Fail,
+    /// Yield the field data up to the limit, then end the field and discard
+    /// the rest of its data.
+    Truncate,
+    /// End the field right away and discard the rest of its data, including
+    /// the chunk which exceeded the limit.
+    Skip,
+}
@@ -1,14 +1,22 @@
 use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use http::header::{self, HeaderMap};
 
 use crate::constants;
+use crate::helpers::bytes_to_human;
 
 /// Represents size limit of the stream to prevent DoS attacks.
 ///
 /// Please refer [`Constraints`](crate::Constraints) for more info.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SizeLimit {
     pub(crate) whole_stream: u64,
     pub(crate) per_field: u64,
+    pub(crate) preamble: u64,
+    pub(crate) headers: u64,
+    pub(crate) text_fields: Option<u64>,
+    pub(crate) file_fields: Option<u64>,
     pub(crate) field_map: HashMap<String, u64>,
 }
 
@@ -19,6 +27,38 @@ impl SizeLimit {
         SizeLimit::default()
     }
 
+    /// Creates a default size limit with the whole stream limited to the value
+    /// of the `Content-Length` header in `headers`.
+    ///
+    /// If the header is missing or malformed, the whole stream limit is
+    /// [`u64::MAX`].
+    ///
+    /// Note that clients can lie about `Content-Length`, so this is only a
+    /// sanity check and not a substitute for explicit limits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http::header::{HeaderMap, HeaderValue, CONTENT_LENGTH};
+    /// use multer::SizeLimit;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(CONTENT_LENGTH, HeaderValue::from_static("1024"));
+    ///
+    /// let size_limit = SizeLimit::whole_stream_from_content_length(&headers).per_field(512);
+    /// ```
+    pub fn whole_stream_from_content_length(headers: &HeaderMap) -> SizeLimit {
+        let content_length = headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.trim().parse::<u64>().ok());
+
+        match content_length {
+            Some(limit) => SizeLimit::new().whole_stream(limit),
+            None => SizeLimit::new(),
+        }
+    }
+
     /// Sets size limit for the whole stream.
     pub fn whole_stream(mut self, limit: u64) -> SizeLimit {
         self.whole_stream = limit;
@@ -31,8 +71,52 @@ impl SizeLimit {
         self
     }
 
+    /// Sets size limit for the preamble, i.e. the data preceding the first
+    /// boundary which is otherwise ignored.
+    pub fn preamble(mut self, limit: u64) -> SizeLimit {
+        self.preamble = limit;
+        self
+    }
+
+    /// Sets size limit for the headers of each field, including the blank
+    /// line ending them.
+    ///
+    /// It prevents attackers from sending endless header lines, which are
+    /// buffered in memory until they are parsed.
+    pub fn headers(mut self, limit: u64) -> SizeLimit {
+        self.headers = limit;
+        self
+    }
+
+    /// Sets size limit for the text fields, i.e. the fields without a
+    /// `filename` parameter in their `Content-Disposition` header. It
+    /// overrides the [`per_field`](Self::per_field) value for these fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::SizeLimit;
+    ///
+    /// let size_limit = SizeLimit::new()
+    ///     .for_text_fields(64 * 1024)
+    ///     .for_file_fields(100 * 1024 * 1024);
+    /// ```
+    pub fn for_text_fields(mut self, limit: u64) -> SizeLimit {
+        self.text_fields = Some(limit);
+        self
+    }
+
+    /// Sets size limit for the file fields, i.e. the fields with a `filename`
+    /// parameter in their `Content-Disposition` header. It overrides the
+    /// [`per_field`](Self::per_field) value for these fields.
+    pub fn for_file_fields(mut self, limit: u64) -> SizeLimit {
+        self.file_fields = Some(limit);
+        self
+    }
+
     /// Sets size limit for a specific field, it overrides the
-    /// [`per_field`](Self::per_field) value for this field.
+    /// [`per_field`](Self::per_field), [`for_text_fields`](Self::for_text_fields)
+    /// and [`for_file_fields`](Self::for_file_fields) values for this field.
     ///
     /// It is useful when you want to set a size limit on a textual field which
     /// will be stored in memory to avoid potential DoS attacks from
@@ -42,19 +126,135 @@ impl SizeLimit {
         self
     }
 
-    pub(crate) fn extract_size_limit_for(&self, field: Option<&str>) -> u64 {
+    /// Iterate over the size limits set for specific fields with
+    /// [`for_field`](Self::for_field), ordered by field name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::SizeLimit;
+    ///
+    /// let size_limit = SizeLimit::new().for_field("b", 2).for_field("a", 1);
+    ///
+    /// assert!(size_limit.has_per_field_overrides());
+    /// assert_eq!(size_limit.per_field_limits().collect::<Vec<_>>(), [("a", 1), ("b", 2)]);
+    /// ```
+    pub fn per_field_limits(&self) -> impl Iterator<Item = (&str, u64)> {
+        let mut limits = self
+            .field_map
+            .iter()
+            .map(|(name, limit)| (name.as_str(), *limit))
+            .collect::<Vec<_>>();
+        limits.sort_unstable();
+        limits.into_iter()
+    }
+
+    /// Whether any size limit is set for a specific field with
+    /// [`for_field`](Self::for_field).
+    pub fn has_per_field_overrides(&self) -> bool {
+        !self.field_map.is_empty()
+    }
+
+    /// Whether all the limits are the default ones, i.e. nothing is limited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::SizeLimit;
+    ///
+    /// assert!(SizeLimit::new().is_default());
+    /// assert!(!SizeLimit::new().per_field(1024).is_default());
+    /// ```
+    pub fn is_default(&self) -> bool {
+        self.whole_stream == constants::DEFAULT_WHOLE_STREAM_SIZE_LIMIT
+            && self.per_field == constants::DEFAULT_PER_FIELD_SIZE_LIMIT
+            && self.preamble == constants::DEFAULT_PREAMBLE_SIZE_LIMIT
+            && self.headers == constants::DEFAULT_HEADERS_SIZE_LIMIT
+            && self.text_fields.is_none()
+            && self.file_fields.is_none()
+            && self.field_map.is_empty()
+    }
+
+    pub(crate) fn extract_size_limit_for(&self, field: Option<&str>, is_file: bool) -> u64 {
+        let kind_limit = if is_file { self.file_fields } else { self.text_fields };
+
         field
-            .and_then(|field| self.field_map.get(&field.to_owned()))
+            .and_then(|field| self.field_map.get(field))
             .copied()
+            .or(kind_limit)
             .unwrap_or(self.per_field)
     }
 }
 
+/// Formats the limits with human-readable units.
+///
+/// # Examples
+///
+/// ```
+/// use multer::SizeLimit;
+///
+/// let size_limit = SizeLimit::new()
+///     .whole_stream(100 * 1024 * 1024)
+///     .per_field(1024 * 1024)
+///     .for_field("avatar", 5 * 1024 * 1024);
+///
+/// assert_eq!(
+///     size_limit.to_string(),
+///     "SizeLimit { whole_stream: 100 MiB, per_field: 1 MiB, preamble: unlimited, headers: unlimited, \
+///      field_overrides: { \"avatar\": 5 MiB } }"
+/// );
+/// ```
+impl Display for SizeLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fn limit(limit: u64) -> String {
+            if limit == u64::MAX {
+                "unlimited".to_owned()
+            } else {
+                bytes_to_human(limit)
+            }
+        }
+
+        write!(
+            f,
+            "SizeLimit {{ whole_stream: {}, per_field: {}, preamble: {}, headers: {}",
+            limit(self.whole_stream),
+            limit(self.per_field),
+            limit(self.preamble),
+            limit(self.headers)
+        )?;
+
+        if let Some(text_fields) = self.text_fields {
+            write!(f, ", text_fields: {}", limit(text_fields))?;
+        }
+
+        if let Some(file_fields) = self.file_fields {
+            write!(f, ", file_fields: {}", limit(file_fields))?;
+        }
+
+        write!(f, ", field_overrides: {{")?;
+
+        for (idx, (name, size)) in self.per_field_limits().enumerate() {
+            let sep = if idx == 0 { " " } else { ", " };
+            write!(f, "{}{:?}: {}", sep, name, limit(size))?;
+        }
+
+        if !self.has_per_field_overrides() {
+            write!(f, "}} }}")
+        } else {
+            write!(f, " }} }}")
+        }
+    }
+}
+
 impl Default for SizeLimit {
     fn default() -> Self {
         SizeLimit {
             whole_stream: constants::DEFAULT_WHOLE_STREAM_SIZE_LIMIT,
             per_field: constants::DEFAULT_PER_FIELD_SIZE_LIMIT,
+            preamble: constants::DEFAULT_PREAMBLE_SIZE_LIMIT,
+            headers: constants::DEFAULT_HEADERS_SIZE_LIMIT,
+            text_fields: None,
+            file_fields: None,
             field_map: HashMap::default(),
         }
     }
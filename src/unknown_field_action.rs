@@ -0,0 +1,14 @@
+/// What to do with a field not in the
+/// [`allowed_fields`](crate::Constraints::allowed_fields), set with
+/// [`Constraints::on_unknown_field()`](crate::Constraints::on_unknown_field).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownFieldAction {
+    /// Fail with [`Error::UnknownField`](crate::Error::UnknownField).
+    #[default]
+    Fail,
+    /// Silently discard the field and yield the next one.
+    Skip,
+    /// Yield the field anyway, logging a warning with the `log` feature.
+    Warn,
+}
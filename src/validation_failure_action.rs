@@ -0,0 +1,12 @@
+/// What to do with a field rejected by the validator set with
+/// [`Constraints::with_field_validator()`](crate::Constraints::with_field_validator).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationFailureAction {
+    /// Fail with
+    /// [`Error::FieldValidationFailed`](crate::Error::FieldValidationFailed).
+    #[default]
+    Fail,
+    /// Silently discard the field and yield the next one.
+    Skip,
+}
@@ -0,0 +1,13 @@
+/// The version of the multipart parsing behaviour.
+///
+/// It is set with [`Constraints::version()`](crate::Constraints::version) and
+/// allows future releases to change the parsing behaviour without breaking
+/// existing users, who keep the version they opted into.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Version {
+    /// The `multipart/form-data` parsing as described in
+    /// [RFC 7578](https://tools.ietf.org/html/rfc7578).
+    #[default]
+    V1,
+}
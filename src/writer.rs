@@ -0,0 +1,194 @@
+use bytes::Bytes;
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+
+use crate::{constants, helpers, BoxStream, Error};
+
+/// An encoder of `multipart/form-data` bodies, the counterpart of
+/// [`Multipart`](crate::Multipart).
+///
+/// Fields are added in order, then [`finish()`](Self::finish) returns the
+/// encoded body as a [`Bytes`] stream. The data of file fields is streamed,
+/// not buffered.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use bytes::Bytes;
+/// use futures_util::stream::once;
+/// use multer::{Multipart, MultipartWriter};
+///
+/// # async fn run() {
+/// let file = once(async { Result::<Bytes, Infallible>::Ok(Bytes::from("Hello world")) });
+///
+/// let mut writer = MultipartWriter::new("X-BOUNDARY").unwrap();
+/// writer
+///     .add_field("my_text_field", "abcd")
+///     .add_file("my_file_field", "a-text-file.txt", mime::TEXT_PLAIN, file);
+///
+/// let mut multipart = Multipart::new(writer.finish(), "X-BOUNDARY");
+///
+/// let field = multipart.next_field().await.unwrap().unwrap();
+/// assert_eq!(field.name(), Some("my_text_field"));
+/// assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+///
+/// let field = multipart.next_field().await.unwrap().unwrap();
+/// assert_eq!(field.file_name(), Some("a-text-file.txt"));
+/// assert_eq!(field.read_to_string().await.unwrap(), "Hello world");
+/// # }
+/// # tokio::runtime::Runtime::new().unwrap().block_on(run());
+/// ```
+pub struct MultipartWriter<'w> {
+    boundary: String,
+    parts: Vec<BoxStream<'w>>,
+}
+
+impl<'w> MultipartWriter<'w> {
+    /// Creates an encoder separating the fields with the given boundary.
+    ///
+    /// The same boundary is to be set in the `Content-Type` header of the
+    /// body, e.g. `multipart/form-data; boundary=X-BOUNDARY`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`Error::InvalidBoundary`] if the boundary doesn't match the
+    /// [RFC 2046](https://tools.ietf.org/html/rfc2046#section-5.1.1) grammar.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use multer::MultipartWriter;
+    ///
+    /// assert!(MultipartWriter::new("X-BOUNDARY").is_ok());
+    /// assert!(MultipartWriter::new("X-BOUNDARY\r\nX-Injected: 1").is_err());
+    /// ```
+    pub fn new<B: Into<String>>(boundary: B) -> crate::Result<MultipartWriter<'w>> {
+        let boundary = boundary.into();
+
+        if !helpers::is_valid_boundary(&boundary) {
+            return Err(Error::InvalidBoundary { boundary });
+        }
+
+        Ok(MultipartWriter {
+            boundary,
+            parts: Vec::new(),
+        })
+    }
+
+    /// Adds a field with the given name and data.
+    pub fn add_field<N, D>(&mut self, name: N, data: D) -> &mut MultipartWriter<'w>
+    where
+        N: AsRef<str>,
+        D: Into<Bytes>,
+    {
+        let headers = self.part_headers(name.as_ref(), None, None);
+        let data = data.into();
+
+        self.parts.push(Box::pin(stream::iter(vec![
+            Ok(headers),
+            Ok(data),
+            Ok(Bytes::from_static(constants::CRLF.as_bytes())),
+        ])));
+        self
+    }
+
+    /// Adds a file field with the given name, file name, content type and
+    /// data stream.
+    ///
+    /// Errors yielded by `data` are forwarded by the encoded stream as
+    /// [`Error::StreamReadFailed`].
+    pub fn add_file<N, F, S, O, E>(
+        &mut self,
+        name: N,
+        file_name: F,
+        content_type: mime::Mime,
+        data: S,
+    ) -> &mut MultipartWriter<'w>
+    where
+        N: AsRef<str>,
+        F: AsRef<str>,
+        S: Stream<Item = Result<O, E>> + Send + 'w,
+        O: Into<Bytes> + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'w,
+    {
+        let headers = self.part_headers(name.as_ref(), Some(file_name.as_ref()), Some(&content_type));
+        let data = data
+            .map_ok(|b| b.into())
+            .map_err(|err| Error::StreamReadFailed(err.into()));
+
+        self.parts
+            .push(Box::pin(stream::once(async { Ok(headers) }).chain(data).chain(
+                stream::once(async { Ok(Bytes::from_static(constants::CRLF.as_bytes())) }),
+            )));
+        self
+    }
+
+    /// Returns the encoded body, ending with the closing boundary.
+    pub fn finish(self) -> impl Stream<Item = crate::Result<Bytes>> + Send + 'w {
+        let closing = format!(
+            "{}{}{}{}",
+            constants::BOUNDARY_EXT,
+            self.boundary,
+            constants::BOUNDARY_EXT,
+            constants::CRLF
+        );
+
+        stream::iter(self.parts)
+            .flatten()
+            .chain(stream::once(async { Ok(Bytes::from(closing)) }))
+    }
+
+    fn part_headers(&self, name: &str, file_name: Option<&str>, content_type: Option<&mime::Mime>) -> Bytes {
+        let mut headers = format!(
+            "{}{}{}Content-Disposition: form-data; name=\"{}\"",
+            constants::BOUNDARY_EXT,
+            self.boundary,
+            constants::CRLF,
+            escape_param(name)
+        );
+
+        if let Some(file_name) = file_name {
+            headers.push_str(&format!("; filename=\"{}\"", escape_param(file_name)));
+        }
+
+        if let Some(content_type) = content_type {
+            headers.push_str(&format!("{}Content-Type: {}", constants::CRLF, content_type));
+        }
+
+        headers.push_str(constants::CRLF_CRLF);
+        Bytes::from(headers)
+    }
+}
+
+impl std::fmt::Debug for MultipartWriter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultipartWriter")
+            .field("boundary", &self.boundary)
+            .field("parts", &self.parts.len())
+            .finish()
+    }
+}
+
+/// Escapes a quoted `Content-Disposition` parameter value, percent-encoding
+/// line breaks as browsers do so that they can't end the header.
+fn escape_param(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_param() {
+        assert_eq!(escape_param("my_field"), "my_field");
+        assert_eq!(escape_param("a \"quoted\" name"), "a \\\"quoted\\\" name");
+        assert_eq!(escape_param("line\r\nbreak"), "line%0D%0Abreak");
+        assert_eq!(escape_param("back\\slash\\"), "back\\\\slash\\\\");
+    }
+}
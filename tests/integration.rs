@@ -1,6 +1,9 @@
 use bytes::Bytes;
 use futures_util::{stream, Stream};
-use multer::{Constraints, Multipart, SizeLimit};
+use multer::{
+    ChunkedMultipart, Constraints, FieldConstraints, Multipart, MultipartWriter, SizeExceededAction, SizeLimit,
+    StreamingStage, ValidationFailureAction,
+};
 
 fn str_stream(string: &'static str) -> impl Stream<Item = multer::Result<Bytes>> {
     stream::iter(
@@ -20,18 +23,25 @@ async fn test_multipart_basic() {
     while let Some((idx, field)) = m.next_field_with_idx().await.unwrap() {
         if idx == 0 {
             assert_eq!(field.name(), Some("my_text_field"));
+            assert_eq!(field.content_disposition_type(), Some("form-data"));
             assert_eq!(field.file_name(), None);
+            assert_eq!(field.file_extension(), None);
             assert_eq!(field.content_type(), None);
             assert_eq!(field.index(), 0);
 
-            assert_eq!(field.text().await, Ok("abcd".to_owned()));
+            assert_eq!(field.read_to_string().await, Ok("abcd".to_owned()));
         } else if idx == 1 {
             assert_eq!(field.name(), Some("my_file_field"));
             assert_eq!(field.file_name(), Some("a-text-file.txt"));
+            assert_eq!(field.file_extension(), Some("txt"));
             assert_eq!(field.content_type(), Some(&mime::TEXT_PLAIN));
+            assert_eq!(field.raw_content_type_str(), Some("text/plain"));
             assert_eq!(field.index(), 1);
 
-            assert_eq!(field.text().await, Ok("Hello world\nHello\r\nWorld\rAgain".to_owned()));
+            assert_eq!(
+                field.read_to_string().await,
+                Ok("Hello world\nHello\r\nWorld\rAgain".to_owned())
+            );
         }
     }
 }
@@ -46,6 +56,24 @@ async fn test_multipart_empty() {
     assert!(m.next_field().await.unwrap().is_none());
 }
 
+#[tokio::test]
+async fn test_multipart_incomplete_stream_stage() {
+    let mut m = Multipart::new(str_stream("preamble"), "X-BOUNDARY");
+    assert_eq!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::IncompleteStream {
+            stage: StreamingStage::FindingFirstBoundary
+        }
+    );
+
+    let mut m = Multipart::new(str_stream("--X-BOUNDARY\r\nContent-Disposition"), "X-BOUNDARY");
+    let err = m.next_field().await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "incomplete multipart stream (ended during ReadingFieldHeaders)"
+    );
+}
+
 #[tokio::test]
 async fn test_multipart_clean_field() {
     let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
@@ -90,7 +118,7 @@ async fn test_multipart_header() {
         let mut m = Multipart::new(stream, "X-BOUNDARY");
 
         assert_eq!(
-            m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+            m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
             "abcd".to_owned()
         );
     }
@@ -105,11 +133,11 @@ async fn test_multipart_constraint_allowed_fields_normal() {
     let mut m = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
 
     assert_eq!(
-        m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
         "abcd".to_owned()
     );
     assert_eq!(
-        m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
         "Hello world\nHello\r\nWorld\rAgain".to_owned()
     );
 }
@@ -140,11 +168,11 @@ async fn test_multipart_constraint_size_limit_whole_stream() {
     let mut m = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
 
     assert_eq!(
-        m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
         "abcd".to_owned()
     );
     assert_eq!(
-        m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
         "Hello world\nHello\r\nWorld\rAgain".to_owned()
     );
 }
@@ -178,11 +206,11 @@ async fn test_multipart_constraint_size_limit_per_field() {
     let mut m = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
 
     assert_eq!(
-        m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
         "abcd".to_owned()
     );
     assert_eq!(
-        m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
         "Hello world\nHello\r\nWorld\rAgain".to_owned()
     );
 }
@@ -222,11 +250,11 @@ async fn test_multipart_constraint_size_limit_for_field() {
     let mut m = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
 
     assert_eq!(
-        m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
         "abcd".to_owned()
     );
     assert_eq!(
-        m.next_field().await.unwrap().unwrap().text().await.unwrap(),
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
         "Hello world\nHello\r\nWorld\rAgain".to_owned()
     );
 }
@@ -266,3 +294,1538 @@ async fn test_multiaccess_caught() {
     assert!(matches!(field2.unwrap_err(), multer::Error::LockFailure));
     assert!(field1.is_ok());
 }
+
+#[tokio::test]
+async fn test_multipart_stream_size_consumed() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+    let stream = str_stream(data);
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().whole_stream(248));
+    let mut m = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
+
+    assert_eq!(m.whole_stream_limit(), 248);
+    assert_eq!(m.stream_size_consumed(), 0);
+
+    while let Some(field) = m.next_field().await.unwrap() {
+        field.read_to_end().await.unwrap();
+    }
+
+    assert_eq!(m.stream_size_consumed(), data.len() as u64);
+}
+
+#[tokio::test]
+async fn test_multipart_drain_and_count() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+
+    let m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    assert_eq!(m.drain_and_count().await.unwrap(), data.len() as u64);
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let field = m.next_field().await.unwrap().unwrap();
+    assert!(m.stream_size_consumed() > 0);
+    drop(field);
+    assert_eq!(m.drain_and_count().await.unwrap(), data.len() as u64);
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let _field = m.next_field().await.unwrap().unwrap();
+    assert!(matches!(m.drain_and_count().await, Err(multer::Error::LockFailure)));
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().whole_stream(100));
+    let m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(matches!(
+        m.drain_and_count().await,
+        Err(multer::Error::StreamSizeExceeded { limit: 100 })
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_deferred_stream() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+    let chunks = data
+        .as_bytes()
+        .chunks(3)
+        .map(Bytes::copy_from_slice)
+        .collect::<Vec<_>>();
+
+    // Each chunk is only produced after the task has been woken by a timer.
+    let stream = stream::unfold(chunks.into_iter(), |mut chunks| async move {
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        chunks.next().map(|chunk| (Ok::<_, multer::Error>(chunk), chunks))
+    });
+
+    let mut m = Multipart::new(stream, "X-BOUNDARY");
+
+    let parse = async {
+        // Leave the first field unread so `next_field()` has to drain it.
+        let field = m.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("my_text_field"));
+        drop(field);
+
+        let mut field = m.next_field().await.unwrap().unwrap();
+        let mut content = Vec::new();
+        while let Some(chunk) = field.chunk().await.unwrap() {
+            content.extend_from_slice(&chunk);
+        }
+
+        assert_eq!(content, b"Hello world\nHello\r\nWorld\rAgain");
+        drop(field);
+
+        assert!(m.next_field().await.unwrap().is_none());
+    };
+
+    tokio::time::timeout(std::time::Duration::from_secs(10), parse)
+        .await
+        .expect("parsing a deferred stream stalled");
+}
+
+// A file upload from a slow client used to end early: `field.chunk()`
+// returned `Ok(None)` when the stream was pending instead of waiting for more
+// data, silently truncating the file.
+#[tokio::test]
+async fn test_field_chunk_slow_client_file_upload() {
+    let video = (0..100 * 1024).map(|idx| (idx % 251) as u8).collect::<Vec<_>>();
+
+    let mut data = b"--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"video\"; filename=\"clip.mp4\"\r\nContent-Type: video/mp4\r\n\r\n".to_vec();
+    data.extend_from_slice(&video);
+    data.extend_from_slice(b"\r\n--X-BOUNDARY--\r\n");
+
+    // Each byte is only produced after the stream has been pending once.
+    let stream = stream::unfold(data.into_iter(), |mut data| async move {
+        tokio::task::yield_now().await;
+        data.next()
+            .map(|byte| (Ok::<_, multer::Error>(Bytes::copy_from_slice(&[byte])), data))
+    });
+
+    let mut m = Multipart::new(stream, "X-BOUNDARY");
+
+    let parse = async {
+        let mut field = m.next_field().await.unwrap().unwrap();
+        assert_eq!(field.file_name(), Some("clip.mp4"));
+
+        let mut content = Vec::new();
+        while let Some(chunk) = field.chunk().await.unwrap() {
+            content.extend_from_slice(&chunk);
+        }
+
+        assert_eq!(content.len(), video.len());
+        assert_eq!(content, video);
+        drop(field);
+
+        assert!(m.next_field().await.unwrap().is_none());
+    };
+
+    tokio::time::timeout(std::time::Duration::from_secs(30), parse)
+        .await
+        .expect("parsing a slow stream stalled");
+}
+
+#[tokio::test]
+async fn test_multipart_mixed() {
+    let content_type = "multipart/mixed; boundary=X-BOUNDARY";
+    let data = "--X-BOUNDARY\r\nContent-Type: text/plain\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Type: application/json\r\n\r\n{}\r\n--X-BOUNDARY--\r\n";
+
+    assert!(multer::parse_boundary(content_type).is_err());
+    let boundary = multer::extract_boundary_from_str(content_type).unwrap();
+    let mut m = Multipart::new(str_stream(data), boundary);
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), None);
+    assert_eq!(field.content_type(), Some(&mime::TEXT_PLAIN));
+    assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.content_type(), Some(&mime::APPLICATION_JSON));
+    assert_eq!(field.read_to_string().await.unwrap(), "{}");
+
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_max_fields_per_name() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"color\"\r\n\r\nred\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"color\"\r\n\r\nblue\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"color\"\r\n\r\ngreen\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new().max_fields_per_name("color", 3);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+
+    let mut count = 0;
+    while m.next_field().await.unwrap().is_some() {
+        count += 1;
+    }
+
+    assert_eq!(count, 3);
+
+    let constraints = Constraints::new().max_fields_per_name("color", 2);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::DuplicateFieldLimitExceeded { limit: 2, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_max_fields() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"color\"\r\n\r\nred\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"size\"\r\n\r\nXL\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new().max_fields(2);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let constraints = Constraints::new().max_fields(1);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::FieldCountExceeded { limit: 1 }
+    ));
+
+    // The limit applies before the headers of the extra field are read.
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"color\"\r\n\r\nred\r\n--X-BOUNDARY\r\nContent-Disp";
+    let constraints = Constraints::new().max_fields(1);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::FieldCountExceeded { limit: 1 }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_max_name_lengths() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a-text-file.txt\"\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new().max_field_name_length(13).max_filename_length(15);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let constraints = Constraints::new().max_field_name_length(12);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    let err = m.next_field().await.unwrap_err();
+    assert!(matches!(
+        &err,
+        multer::Error::FieldNameTooLong { limit: 12, name } if name == "my_text_field"
+    ));
+    assert_eq!(m.next_field().await.unwrap().unwrap().name(), Some("file"));
+
+    let constraints = Constraints::new().max_filename_length(14);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::FileNameTooLong { limit: 14, name } if name == "a-text-file.txt"
+    ));
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_size_limit_preamble() {
+    let data = "ignored header\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().preamble(16));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().preamble(15));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::PreambleSizeExceeded { limit: 15 }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_size_limit_headers() {
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().headers(56));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().headers(55));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::HeaderSizeExceeded { limit: 55 }
+    ));
+
+    // The limit applies before the end of the headers is found.
+    let data = "--X-BOUNDARY\r\nX-Junk: a\r\nX-Junk: a\r\nX-Junk: a\r\nX-Junk: a\r\n";
+    let constraints = Constraints::new().size_limit(SizeLimit::new().headers(32));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::HeaderSizeExceeded { limit: 32 }
+    ));
+}
+
+#[tokio::test]
+async fn test_field_text_streaming() {
+    use futures_util::TryStreamExt;
+
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nকখগ-你好\r\n--X-BOUNDARY--\r\n";
+    let stream = stream::iter(
+        data.as_bytes()
+            .iter()
+            .map(|b| Ok::<_, multer::Error>(Bytes::copy_from_slice(&[*b]))),
+    );
+    let mut m = Multipart::new(stream, "X-BOUNDARY");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    let parts: Vec<String> = field.text_streaming("utf-8").try_collect().await.unwrap();
+    assert_eq!(parts.concat(), "কখগ-你好");
+
+    let data =
+        b"--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nab\xffcd\r\n--X-BOUNDARY--\r\n";
+    let stream = stream::once(async move { Ok::<_, multer::Error>(Bytes::from_static(data)) });
+    let mut m = Multipart::new(stream, "X-BOUNDARY");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    let result: multer::Result<Vec<String>> = field.text_streaming("utf-8").try_collect().await;
+    assert!(matches!(result.unwrap_err(), multer::Error::DecodeText { .. }));
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_any_field() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+    let stream = str_stream(data);
+
+    let constraints = Constraints::new().allowed_fields(vec!["my_text_field"]).any_field();
+    let mut m = Multipart::with_constraints(stream, "X-BOUNDARY", constraints);
+
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_try_for_each() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+
+    let mut names = Vec::new();
+    let m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    m.try_for_each(|field| {
+        names.push(field.name().unwrap().to_owned());
+        async move { field.read_to_end().await.map(drop) }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(names, ["my_text_field", "my_file_field"]);
+
+    let mut count = 0;
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let result = m
+        .try_for_each_ref(|_| {
+            count += 1;
+            async {
+                Err(multer::Error::IncompleteStream {
+                    stage: StreamingStage::ReadingFieldData,
+                })
+            }
+        })
+        .await;
+
+    assert_eq!(
+        result,
+        Err(multer::Error::IncompleteStream {
+            stage: StreamingStage::ReadingFieldData
+        })
+    );
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn test_multipart_flat_map_fields() {
+    use futures_util::TryStreamExt;
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+    let m = Multipart::new(str_stream(data), "X-BOUNDARY");
+
+    let lengths: Vec<(usize, usize)> = m
+        .flat_map_fields(|field| {
+            let idx = field.index();
+            field.map_ok(move |chunk| (idx, chunk.len()))
+        })
+        .try_collect()
+        .await
+        .unwrap();
+
+    let total = |idx| {
+        lengths
+            .iter()
+            .filter(|(i, _)| *i == idx)
+            .map(|(_, len)| len)
+            .sum::<usize>()
+    };
+    assert_eq!(total(0), 4);
+    assert_eq!(total(1), 30);
+    assert!(lengths.windows(2).all(|w| w[0].0 <= w[1].0));
+}
+
+#[cfg(feature = "derive")]
+#[tokio::test]
+async fn test_multipart_derive_from_multipart() {
+    use multer::FromMultipart;
+
+    #[derive(FromMultipart)]
+    struct Upload {
+        my_text_field: String,
+        my_file_field: Bytes,
+        missing_field: Option<String>,
+    }
+
+    #[derive(FromMultipart)]
+    struct RawUpload {
+        my_file_field: Vec<u8>,
+        my_text_field: Option<Vec<u8>>,
+        missing_field: Vec<Vec<u8>>,
+    }
+
+    #[derive(FromMultipart)]
+    #[allow(dead_code)]
+    struct Strict {
+        my_text_field: String,
+        missing_field: String,
+    }
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+
+    let upload = Upload::from_multipart(Multipart::new(str_stream(data), "X-BOUNDARY"))
+        .await
+        .unwrap();
+    assert_eq!(upload.my_text_field, "abcd");
+    assert_eq!(&upload.my_file_field[..], b"Hello world\nHello\r\nWorld\rAgain");
+    assert_eq!(upload.missing_field, None);
+
+    let upload = RawUpload::from_multipart(Multipart::new(str_stream(data), "X-BOUNDARY"))
+        .await
+        .unwrap();
+    assert_eq!(upload.my_file_field, b"Hello world\nHello\r\nWorld\rAgain");
+    assert_eq!(upload.my_text_field.as_deref(), Some(&b"abcd"[..]));
+    assert!(upload.missing_field.is_empty());
+
+    let result = Strict::from_multipart(Multipart::new(str_stream(data), "X-BOUNDARY")).await;
+    assert!(matches!(
+        result.err().unwrap(),
+        multer::Error::MissingField { field_name } if field_name == "missing_field"
+    ));
+}
+
+#[cfg(feature = "tokio-io")]
+#[tokio::test]
+async fn test_multipart_from_file() {
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    let path = std::env::temp_dir().join(format!("multer-test-from-file-{}", std::process::id()));
+    tokio::fs::write(&path, data).await.unwrap();
+
+    let mut m = Multipart::from_file(&path, "X-BOUNDARY").await.unwrap();
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert!(m.next_field().await.unwrap().is_none());
+    tokio::fs::remove_file(&path).await.unwrap();
+
+    assert!(matches!(
+        Multipart::from_file(&path, "X-BOUNDARY").await.unwrap_err(),
+        multer::Error::StreamReadFailed(_)
+    ));
+}
+
+#[cfg(feature = "tokio-io")]
+#[tokio::test]
+async fn test_field_copy_to_writer() {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"\r\n\r\nefgh\r\n--X-BOUNDARY--\r\n";
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+
+    let writer = SharedWriter::default();
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.copy_to_writer(writer.clone()).await.unwrap(), 4);
+    assert_eq!(*writer.0.lock().unwrap(), b"abcd");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    let err = field.copy_to_writer(std::io::Cursor::new([0u8; 2])).await;
+    assert!(matches!(err, Err(multer::Error::WriteFailed(_))));
+}
+
+#[tokio::test]
+async fn test_field_eq() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+    let other =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nother\r\n--X-BOUNDARY--\r\n";
+
+    let mut m1 = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let mut m2 = Multipart::new(str_stream(other), "X-BOUNDARY");
+
+    let field1 = m1.next_field().await.unwrap().unwrap();
+    let field2 = m2.next_field().await.unwrap().unwrap();
+    assert_eq!(field1, field2);
+    drop((field1, field2));
+
+    let field1 = m1.next_field().await.unwrap().unwrap();
+    let mut m2 = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let field2 = m2.next_field().await.unwrap().unwrap();
+    assert_ne!(field1, field2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_multipart_fields_processed_in_spawned_tasks() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+
+    let handle = tokio::spawn(async move {
+        let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+        let mut contents = Vec::new();
+
+        // Only one field may be live at a time, so each spawned task must
+        // finish with its field before the next one is requested.
+        while let Some(field) = m.next_field().await.unwrap() {
+            let task = tokio::spawn(async move {
+                let name = field.name().unwrap().to_owned();
+                (name, field.read_to_string().await.unwrap())
+            });
+
+            contents.push(task.await.unwrap());
+        }
+
+        contents
+    });
+
+    let contents = handle.await.unwrap();
+    assert_eq!(
+        contents,
+        [
+            ("my_text_field".to_owned(), "abcd".to_owned()),
+            (
+                "my_file_field".to_owned(),
+                "Hello world\nHello\r\nWorld\rAgain".to_owned()
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_for_named_field() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new().for_named_field(
+        "my_file_field",
+        FieldConstraints::new()
+            .max_size(30)
+            .require_filename()
+            .allowed_types(vec![mime::TEXT_STAR]),
+    );
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(m.next_field().await.unwrap().is_some());
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "Hello world\nHello\r\nWorld\rAgain"
+    );
+
+    let constraints = Constraints::new().for_named_field("my_file_field", FieldConstraints::new().max_size(10));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(matches!(
+        m.next_field()
+            .await
+            .unwrap()
+            .unwrap()
+            .read_to_string()
+            .await
+            .unwrap_err(),
+        multer::Error::FieldSizeExceeded { limit: 10, .. }
+    ));
+
+    let constraints = Constraints::new().for_named_field("my_text_field", FieldConstraints::new().require_filename());
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::MissingFileName { .. }
+    ));
+
+    let constraints = Constraints::new().for_named_field(
+        "my_file_field",
+        FieldConstraints::new().allowed_types(vec![mime::IMAGE_PNG]),
+    );
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::ContentTypeNotAllowed { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_allowed_content_types_for_field() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"a.png\"\r\nContent-Type: image/png\r\n\r\nPNG\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"a.gif\"\r\nContent-Type: image/gif\r\n\r\nGIF\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new()
+        .for_named_field("avatar", FieldConstraints::new().max_size(3))
+        .allowed_content_types_for_field("avatar", vec![mime::IMAGE_JPEG, mime::IMAGE_PNG]);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "PNG"
+    );
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::ContentTypeNotAllowed { content_type: Some(content_type), .. } if content_type == "image/gif"
+    ));
+}
+
+#[tokio::test]
+async fn test_chunked_multipart_append_stream() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+    let (first, second) = data.split_at(data.find("World").unwrap());
+
+    let mut m = ChunkedMultipart::new("X-BOUNDARY");
+    m.append_stream(str_stream(first));
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+
+    // The rest of the second field hasn't been appended yet.
+    let mut field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("my_file_field"));
+    let mut content = Vec::new();
+    let wait = async {
+        while let Some(chunk) = field.chunk().await.unwrap() {
+            content.extend_from_slice(&chunk);
+        }
+    };
+    assert!(tokio::time::timeout(std::time::Duration::from_millis(50), wait)
+        .await
+        .is_err());
+
+    m.append_stream(str_stream(second));
+    m.finish();
+
+    while let Some(chunk) = field.chunk().await.unwrap() {
+        content.extend_from_slice(&chunk);
+    }
+    assert_eq!(content, b"Hello world\nHello\r\nWorld\rAgain");
+    drop(field);
+
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_chunked_multipart_appender() {
+    use futures_util::FutureExt;
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+    let (first, second) = data.split_at(
+        data.find("Content-Disposition: form-data; name=\"my_file_field\"")
+            .unwrap(),
+    );
+
+    let mut m = ChunkedMultipart::new("X-BOUNDARY");
+    m.append_stream(str_stream(first));
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+
+    // The first segment ends between the fields, so the next field is awaited
+    // until the spawned task appends the rest.
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let appender = m.appender();
+    let append = tokio::spawn(async move {
+        rx.await.unwrap();
+        appender.append_stream(str_stream(second));
+        appender.finish();
+    });
+
+    let mut next_field = Box::pin(m.next_field());
+    assert!(next_field.as_mut().now_or_never().is_none());
+    tx.send(()).unwrap();
+
+    let field = next_field.await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("my_file_field"));
+    assert_eq!(field.read_to_string().await.unwrap(), "Hello world");
+    append.await.unwrap();
+
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_skip_unread_field() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    drop(m.next_field().await.unwrap().unwrap());
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("my_file_field"));
+    drop(field);
+    assert!(m.next_field().await.unwrap().is_none());
+
+    // Skipped data still counts towards the field size limit.
+    let constraints = Constraints::new().size_limit(SizeLimit::new().for_field("my_file_field", 10));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    drop(m.next_field().await.unwrap().unwrap());
+    drop(m.next_field().await.unwrap().unwrap());
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::FieldSizeExceeded { limit: 10, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_on_chunk_received() {
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+
+    let chunks = Arc::new(AtomicUsize::new(0));
+    let received = Arc::new(AtomicU64::new(0));
+    let constraints = Constraints::new().on_chunk_received({
+        let (chunks, received) = (chunks.clone(), received.clone());
+        Arc::new(move |total| {
+            chunks.fetch_add(1, Ordering::SeqCst);
+            assert!(total > received.swap(total, Ordering::SeqCst));
+        })
+    });
+
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    while let Some(field) = m.next_field().await.unwrap() {
+        field.read_to_end().await.unwrap();
+    }
+
+    assert_eq!(chunks.load(Ordering::SeqCst), data.len());
+    assert_eq!(received.load(Ordering::SeqCst), data.len() as u64);
+}
+
+#[tokio::test]
+async fn test_multipart_next_field_result() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new().allowed_fields(vec!["my_file_field"]);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+
+    assert!(matches!(
+        m.next_field_result().await,
+        Some(Err(multer::Error::UnknownField { .. }))
+    ));
+    let field = m.next_field_result().await.unwrap().unwrap();
+    assert_eq!(
+        field.read_to_string().await.unwrap(),
+        "Hello world\nHello\r\nWorld\rAgain"
+    );
+    assert!(m.next_field_result().await.is_none());
+    assert!(m.last_error().is_none());
+
+    let mut m = Multipart::new(str_stream(&data[..data.len() - 20]), "X-BOUNDARY");
+    assert!(m.next_field_result().await.unwrap().is_ok());
+    let field = m.next_field_result().await.unwrap().unwrap();
+    assert!(field.read_to_end().await.is_err());
+    assert!(m.next_field_result().await.is_none());
+    assert!(m.last_error().is_some());
+    assert!(m.next_field_result().await.is_none());
+
+    // Holding on to a field ends the iteration instead of looping.
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let field = m.next_field_result().await.unwrap().unwrap();
+    assert!(m.next_field_result().await.is_none());
+    assert!(matches!(m.last_error(), Some(multer::Error::LockFailure)));
+    drop(field);
+    assert!(m.next_field_result().await.is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_for_boundary() {
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::for_boundary("X-BOUNDARY").unwrap();
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+
+    let constraints = Constraints::for_boundary("Y-BOUNDARY").unwrap();
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::BoundaryMismatch { .. }
+    ));
+
+    assert!(matches!(
+        Constraints::for_boundary("").unwrap_err(),
+        multer::Error::InvalidBoundary { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_has_remaining_fields() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\nHello\r\nWorld\rAgain\r\n--X-BOUNDARY--\r\n";
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+
+    assert!(m.has_remaining_fields().await.unwrap());
+    let field = m.next_field().await.unwrap().unwrap();
+    assert!(m.has_remaining_fields().await.is_err());
+    drop(field);
+
+    // The unread data of the first field is skipped.
+    assert!(m.has_remaining_fields().await.unwrap());
+    assert!(m.has_remaining_fields().await.unwrap());
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.index(), 1);
+    assert_eq!(
+        field.read_to_string().await.unwrap(),
+        "Hello world\nHello\r\nWorld\rAgain"
+    );
+
+    assert!(!m.has_remaining_fields().await.unwrap());
+    assert!(m.next_field().await.unwrap().is_none());
+    assert!(!m.has_remaining_fields().await.unwrap());
+
+    let mut m = Multipart::new(str_stream("--X-BOUNDARY--\r\n"), "X-BOUNDARY");
+    assert!(!m.has_remaining_fields().await.unwrap());
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_allow_missing_final_boundary() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"\r\n\r\nHello\r\nWor";
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert!(m.next_field().await.unwrap().unwrap().read_to_string().await.is_err());
+
+    let constraints = Constraints::new().allow_missing_final_boundary(true);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "Hello\r\nWor"
+    );
+    assert!(m.next_field().await.unwrap().is_none());
+
+    // The unread data of the last field is skipped.
+    let constraints = Constraints::new().allow_missing_final_boundary(true);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(m.next_field().await.unwrap().is_some());
+    assert!(!m.has_remaining_fields().await.unwrap());
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_on_field_size_exceeded() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"truncated\"\r\n\r\nabcdef\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"skipped\"\r\n\r\nabcdef\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"unread\"\r\n\r\nabcdef\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"failed\"\r\n\r\nabcdef\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new()
+        .size_limit(SizeLimit::new().per_field(3))
+        .on_field_size_exceeded(|name, limit| {
+            assert_eq!(limit, 3);
+            match name {
+                "truncated" | "unread" => SizeExceededAction::Truncate,
+                "skipped" => SizeExceededAction::Skip,
+                _ => SizeExceededAction::Fail,
+            }
+        });
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abc"
+    );
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        ""
+    );
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("unread"));
+    drop(field);
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("failed"));
+    assert!(matches!(
+        field.read_to_string().await.unwrap_err(),
+        multer::Error::FieldSizeExceeded { limit: 3, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_new_with_limits() {
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+
+    let mut m = Multipart::new_with_limits(str_stream(data), "X-BOUNDARY", data.len() as u64, 4);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let mut m = Multipart::new_with_limits(str_stream(data), "X-BOUNDARY", data.len() as u64, 3);
+    assert!(m.next_field().await.unwrap().unwrap().read_to_string().await.is_err());
+
+    let mut m = Multipart::new_with_limits(str_stream(data), "X-BOUNDARY", 10, 4);
+    assert!(m.next_field().await.is_err());
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_with_field_validator() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"_hidden\"\r\n\r\nsecret\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b\"; filename=\"b.txt\"\r\n\r\nefgh\r\n--X-BOUNDARY--\r\n";
+    let validator = |field: &multer::Field<'_>| !field.name().unwrap_or_default().starts_with('_');
+
+    let constraints = Constraints::new()
+        .with_field_validator(validator)
+        .on_validation_failure(ValidationFailureAction::Skip);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("b"));
+    assert_eq!(field.read_to_string().await.unwrap(), "efgh");
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let constraints = Constraints::new().with_field_validator(validator);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::FieldValidationFailed { field_name: Some(name) } if name == "_hidden"
+    ));
+    // The rejected field is skipped by the next call.
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "efgh"
+    );
+}
+
+#[tokio::test]
+async fn test_multipart_set_current_field_size_limit() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"\r\n\r\nabcdef\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcdef\r\n--X-BOUNDARY--\r\n";
+    let constraints = Constraints::new().size_limit(SizeLimit::new().per_field(4));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+
+    let mut field = m.next_field().await.unwrap().unwrap();
+    m.set_current_field_size_limit(6).unwrap();
+    assert_eq!(field.chunk().await.unwrap().unwrap(), "abcdef");
+    assert!(matches!(
+        m.set_current_field_size_limit(5),
+        Err(multer::Error::FieldSizeExceeded { limit: 5, .. })
+    ));
+    assert!(field.chunk().await.unwrap().is_none());
+    drop(field);
+
+    // The next field gets the limit from the constraints again.
+    assert!(m.next_field().await.unwrap().unwrap().read_to_string().await.is_err());
+}
+
+#[tokio::test]
+async fn test_multipart_from_stream_with_auto_boundary() {
+    let data =
+        "--X-BOUNDARY \r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    let mut m = Multipart::from_stream_with_auto_boundary(str_stream(data))
+        .await
+        .unwrap();
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let data = "preamble\r\n--X-BOUNDARY\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    let res = Multipart::from_stream_with_auto_boundary(str_stream(data)).await;
+    assert!(matches!(res.unwrap_err(), multer::Error::NoBoundary));
+
+    let res = Multipart::from_stream_with_auto_boundary(str_stream("--X-BOUNDARY")).await;
+    assert!(matches!(res.unwrap_err(), multer::Error::NoBoundary));
+}
+
+#[cfg(feature = "tokio-io")]
+#[tokio::test]
+async fn test_field_into_async_read() {
+    use tokio::io::AsyncReadExt;
+
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+
+    let mut reader = m.next_field().await.unwrap().unwrap().into_async_read();
+    let content = tokio::spawn(async move {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).await.map(|_| content)
+    });
+    assert_eq!(content.await.unwrap().unwrap(), b"abcd");
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().per_field(3));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    let mut reader = m.next_field().await.unwrap().unwrap().into_async_read();
+    let err = reader.read_to_end(&mut Vec::new()).await.unwrap_err();
+    assert!(matches!(
+        err.get_ref().unwrap().downcast_ref::<multer::Error>(),
+        Some(multer::Error::FieldSizeExceeded { limit: 3, .. })
+    ));
+}
+
+#[cfg(feature = "tokio-io")]
+#[tokio::test]
+async fn test_multipart_from_sync_reader_with_runtime() {
+    let content = "a".repeat(10_000);
+    let data = format!(
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"\r\n\r\n{}\r\n--X-BOUNDARY--\r\n",
+        content
+    );
+
+    let reader = std::io::Cursor::new(data.into_bytes());
+    let handle = tokio::runtime::Handle::current();
+    let mut m = Multipart::from_sync_reader_with_runtime(reader, "X-BOUNDARY", &handle);
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        content
+    );
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_writer() {
+    use futures_util::TryStreamExt;
+
+    let mut writer = MultipartWriter::new("X-BOUNDARY").unwrap();
+    writer
+        .add_field("my_text_field", "abcd")
+        .add_field("a \"quoted\" name", "")
+        .add_file(
+            "my_file_field",
+            "a-text-file.txt",
+            mime::TEXT_PLAIN,
+            str_stream("Hello\r\nWorld"),
+        );
+
+    let data = writer
+        .finish()
+        .try_fold(Vec::new(), |mut data, chunk| async move {
+            data.extend_from_slice(&chunk);
+            Ok(data)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(data.clone()).unwrap(),
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n\
+         --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"a \\\"quoted\\\" name\"\r\n\r\n\r\n\
+         --X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\n\
+         Content-Type: text/plain\r\n\r\nHello\r\nWorld\r\n--X-BOUNDARY--\r\n"
+    );
+
+    let mut m = Multipart::new(
+        stream::once(async { Ok::<_, std::convert::Infallible>(data) }),
+        "X-BOUNDARY",
+    );
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("my_text_field"));
+    assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("a \"quoted\" name"));
+    assert_eq!(field.read_to_string().await.unwrap(), "");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("my_file_field"));
+    assert_eq!(field.file_name(), Some("a-text-file.txt"));
+    assert_eq!(field.content_type(), Some(&mime::TEXT_PLAIN));
+    assert_eq!(field.read_to_string().await.unwrap(), "Hello\r\nWorld");
+
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_writer_round_trip_escapes() {
+    let names = ["a\\", "a\"b", "a\\\"b\\\\"];
+
+    let mut writer = MultipartWriter::new("X-BOUNDARY").unwrap();
+    for name in names {
+        writer.add_file(name, name, mime::TEXT_PLAIN, str_stream("abcd"));
+    }
+
+    let mut m = Multipart::new(writer.finish(), "X-BOUNDARY");
+    for name in names {
+        let field = m.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some(name));
+        assert_eq!(field.file_name(), Some(name));
+    }
+
+    assert!(m.next_field().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_writer_stream_error() {
+    use futures_util::TryStreamExt;
+
+    let mut writer = MultipartWriter::new("X-BOUNDARY").unwrap();
+    writer.add_file(
+        "my_file_field",
+        "a-text-file.txt",
+        mime::TEXT_PLAIN,
+        stream::iter(vec![Ok(Bytes::from("abcd")), Err(std::io::Error::other("broken"))]),
+    );
+
+    let err = writer.finish().try_for_each(|_| async { Ok(()) }).await.unwrap_err();
+    assert!(matches!(err, multer::Error::StreamReadFailed(_)));
+}
+
+#[tokio::test]
+async fn test_multipart_stream() {
+    use futures_util::{StreamExt, TryStreamExt};
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\nContent-Type: text/plain\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+
+    let m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let fields: Vec<(Option<String>, String)> = m
+        .and_then(|field| async move {
+            let name = field.name().map(str::to_owned);
+            Ok((name, field.read_to_string().await?))
+        })
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(
+        fields,
+        [
+            (Some("my_text_field".to_owned()), "abcd".to_owned()),
+            (Some("my_file_field".to_owned()), "Hello world".to_owned()),
+        ]
+    );
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let names: Vec<_> = (&mut m)
+        .map(|field| field.unwrap().name().map(str::to_owned))
+        .collect()
+        .await;
+    assert_eq!(
+        names,
+        [Some("my_text_field".to_owned()), Some("my_file_field".to_owned())]
+    );
+    assert!(m.next().await.is_none());
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let field = m.next().await.unwrap().unwrap();
+    assert!(matches!(m.next().await, Some(Err(multer::Error::LockFailure))));
+    drop(field);
+    assert!(m.next().await.is_none());
+
+    // Collecting holds on to the first field, so the stream ends with the
+    // lock failure instead of yielding it forever.
+    let m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let results: Vec<_> = m.collect().await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().name(), Some("my_text_field"));
+    assert!(matches!(results[1], Err(multer::Error::LockFailure)));
+}
+
+#[cfg(debug_assertions)]
+#[tokio::test]
+async fn test_multipart_debug_dump() {
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+
+    drop(m.next_field().await.unwrap().unwrap());
+
+    let dump = m.debug_dump();
+    assert!(dump.contains("stage: ReadingFieldData"));
+    assert!(dump.contains("next_field_idx: 1"));
+}
+
+#[tokio::test]
+async fn test_multipart_stream_ends_after_terminal_error() {
+    use futures_util::StreamExt;
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"legacy_field\"\r\n\r\nold\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"\r\n\r\nunterminated";
+
+    let constraints = Constraints::new().allowed_fields(vec!["my_text_field", "my_file_field"]);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+
+    let mut results = Vec::new();
+    while let Some(result) = m.next().await {
+        results.push(result.map(|field| field.name().map(str::to_owned)));
+    }
+
+    assert_eq!(results.len(), 4);
+    assert!(matches!(results[0], Err(multer::Error::UnknownField { .. })));
+    assert_eq!(results[1], Ok(Some("my_text_field".to_owned())));
+    assert_eq!(results[2], Ok(Some("my_file_field".to_owned())));
+    assert!(matches!(results[3], Err(multer::Error::IncompleteFieldData { .. })));
+    assert!(m.next().await.is_none());
+
+    let mut m = Multipart::new(str_stream("--X-BOUNDARY\r\n"), "X-BOUNDARY");
+    assert!(matches!(
+        m.next().await,
+        Some(Err(multer::Error::IncompleteStream { .. }))
+    ));
+    assert!(m.next().await.is_none());
+}
+
+#[cfg(feature = "tokio-io")]
+#[tokio::test]
+async fn test_field_write_to() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let field = m.next_field().await.unwrap().unwrap();
+    let mut buf = Vec::new();
+    assert_eq!(field.write_to(&mut buf).await.unwrap(), 11);
+    assert_eq!(buf, b"Hello world");
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().per_field(3));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    let field = m.next_field().await.unwrap().unwrap();
+    let err = field.write_to(&mut Vec::new()).await.unwrap_err();
+    assert!(matches!(err, multer::Error::FieldSizeExceeded { limit: 3, .. }));
+}
+
+#[tokio::test]
+async fn test_field_read_failed() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nab";
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<Result<Bytes, std::io::Error>>();
+    let stream = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|res| (res, rx)) });
+
+    let mut m = Multipart::new(stream, "X-BOUNDARY");
+    tx.send(Ok(Bytes::from(data))).unwrap();
+    let mut field = m.next_field().await.unwrap().unwrap();
+
+    tx.send(Err(std::io::Error::other("reset"))).unwrap();
+    let err = field.chunk().await.unwrap_err();
+
+    match &err {
+        multer::Error::FieldReadFailed {
+            field_name,
+            offset,
+            cause,
+        } => {
+            assert_eq!(field_name.as_deref(), Some("my_text_field"));
+            assert_eq!(*offset, data.len() as u64);
+            assert!(matches!(**cause, multer::Error::StreamReadFailed(_)));
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+
+    let io_err = err.inner_error().unwrap().downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(io_err.to_string(), "reset");
+}
+
+#[tokio::test]
+async fn test_field_skip() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    m.next_field().await.unwrap().unwrap().skip().await.unwrap();
+    let mut field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("my_file_field"));
+    assert_eq!(field.chunk().await.unwrap().unwrap(), "Hello world");
+    field.skip().await.unwrap();
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().per_field(3));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(matches!(
+        m.next_field().await.unwrap().unwrap().skip().await.unwrap_err(),
+        multer::Error::FieldSizeExceeded { limit: 3, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_ignored_fields() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"_csrf\"\r\n\r\ntoken\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"_csrf\"\r\n\r\ntoken\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new()
+        .allowed_fields(vec!["my_text_field"])
+        .ignored_fields(vec!["_csrf"]);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.name(), Some("my_text_field"));
+    assert_eq!(field.index(), 1);
+    assert_eq!(field.read_to_string().await.unwrap(), "abcd");
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let constraints = Constraints::new().ignored_fields(vec!["_csrf"]).max_fields(1);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::FieldCountExceeded { limit: 1 }
+    ));
+}
+
+#[tokio::test]
+async fn test_error_kind() {
+    use multer::ErrorKind;
+
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new().size_limit(SizeLimit::new().per_field(3));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    let err = m.next_field().await.unwrap().unwrap().read_to_end().await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::SizeExceeded);
+
+    let constraints = Constraints::new().allowed_fields(vec!["my_file_field"]);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(m.next_field().await.unwrap_err().kind(), ErrorKind::UnknownField);
+
+    let mut m = Multipart::new(str_stream(&data[..40]), "X-BOUNDARY");
+    assert_eq!(m.next_field().await.unwrap_err().kind(), ErrorKind::IncompleteData);
+
+    assert_eq!(
+        multer::parse_boundary("text/plain").unwrap_err().kind(),
+        ErrorKind::ParseFailure
+    );
+}
+
+#[tokio::test]
+async fn test_size_limit_for_text_and_file_fields() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\n\r\nHello world\r\n--X-BOUNDARY--\r\n";
+
+    let size_limit = SizeLimit::new().per_field(1).for_text_fields(4).for_file_fields(11);
+    let mut m = Multipart::with_constraints(
+        str_stream(data),
+        "X-BOUNDARY",
+        Constraints::new().size_limit(size_limit),
+    );
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "Hello world"
+    );
+
+    let size_limit = SizeLimit::new()
+        .for_text_fields(3)
+        .for_field("my_text_field", 4)
+        .for_file_fields(10);
+    let mut m = Multipart::with_constraints(
+        str_stream(data),
+        "X-BOUNDARY",
+        Constraints::new().size_limit(size_limit),
+    );
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_string().await.unwrap(),
+        "abcd"
+    );
+    assert!(matches!(
+        m.next_field()
+            .await
+            .unwrap()
+            .unwrap()
+            .read_to_string()
+            .await
+            .unwrap_err(),
+        multer::Error::FieldSizeExceeded { limit: 10, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_multipart_next_field_named() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_file_field\"; filename=\"a-text-file.txt\"\r\n\r\nHello world\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nefgh\r\n--X-BOUNDARY--\r\n";
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    let field = m.next_field_named("my_file_field").await.unwrap().unwrap();
+    assert_eq!(field.index(), 1);
+    assert_eq!(field.read_to_string().await.unwrap(), "Hello world");
+
+    let field = m.next_field_named("my_text_field").await.unwrap().unwrap();
+    assert_eq!(field.read_to_string().await.unwrap(), "efgh");
+
+    assert!(m.next_field_named("my_text_field").await.unwrap().is_none());
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+    assert!(m.next_field_named("missing").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_field_lines() {
+    use futures_util::TryStreamExt;
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"csv\"\r\n\r\na,b\r\n1,2\n\n3,4\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"empty\"\r\n\r\n\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"trailing\"\r\n\r\nx\n\r\n--X-BOUNDARY--\r\n";
+
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+
+    let lines: Vec<String> = m
+        .next_field()
+        .await
+        .unwrap()
+        .unwrap()
+        .lines()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(lines, ["a,b", "1,2", "", "3,4"]);
+
+    let lines: Vec<String> = m
+        .next_field()
+        .await
+        .unwrap()
+        .unwrap()
+        .lines()
+        .try_collect()
+        .await
+        .unwrap();
+    assert!(lines.is_empty());
+
+    let lines: Vec<String> = m
+        .next_field()
+        .await
+        .unwrap()
+        .unwrap()
+        .lines()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(lines, ["x"]);
+}
+
+#[tokio::test]
+async fn test_multipart_constraint_on_unknown_field() {
+    use multer::UnknownFieldAction;
+
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"legacy_field\"\r\n\r\nold\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"other_field\"\r\n\r\nefgh\r\n--X-BOUNDARY--\r\n";
+
+    let constraints = Constraints::new()
+        .allowed_fields(vec!["my_text_field"])
+        .on_unknown_field(UnknownFieldAction::Skip);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(m.next_field().await.unwrap().unwrap().name(), Some("my_text_field"));
+    assert!(m.next_field().await.unwrap().is_none());
+
+    let constraints = Constraints::new()
+        .allowed_fields(vec!["my_text_field"])
+        .on_unknown_field(UnknownFieldAction::Warn);
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    let mut names = Vec::new();
+    while let Some(field) = m.next_field().await.unwrap() {
+        names.push(field.name().unwrap().to_owned());
+    }
+    assert_eq!(names, ["legacy_field", "my_text_field", "other_field"]);
+
+    let constraints = Constraints::new()
+        .allowed_fields(vec!["my_text_field"])
+        .on_unknown_field_with(|name| match name.starts_with("legacy_") {
+            true => UnknownFieldAction::Skip,
+            false => UnknownFieldAction::Fail,
+        });
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", constraints);
+    assert_eq!(m.next_field().await.unwrap().unwrap().name(), Some("my_text_field"));
+    assert!(matches!(
+        m.next_field().await.unwrap_err(),
+        multer::Error::UnknownField { field_name: Some(name) } if name == "other_field"
+    ));
+}
+
+#[cfg(feature = "transfer-encoding")]
+#[tokio::test]
+async fn test_field_decoded_bytes() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"b64\"\r\nContent-Transfer-Encoding: BASE64\r\n\r\nSGVsbG8g\r\nd29ybGQ=\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"qp\"\r\nContent-Transfer-Encoding: quoted-printable\r\n\r\ncaf=C3=A9\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"plain\"\r\n\r\nabcd\r\n--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"bad\"\r\nContent-Transfer-Encoding: base64\r\n\r\n!!\r\n--X-BOUNDARY--\r\n";
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.content_transfer_encoding(), Some("BASE64"));
+    assert_eq!(field.decoded_bytes().await.unwrap(), "Hello world");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.decoded_bytes().await.unwrap(), "café");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(field.content_transfer_encoding(), None);
+    assert_eq!(field.decoded_bytes().await.unwrap(), "abcd");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    assert_eq!(
+        field.decoded_bytes().await.unwrap_err(),
+        multer::Error::DecodeTransferEncoding {
+            encoding: "base64".to_owned()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_multipart_constraints_clone() {
+    let data =
+        "--X-BOUNDARY\r\nContent-Disposition: form-data; name=\"my_text_field\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+
+    let base = Constraints::new()
+        .allowed_fields(vec!["my_text_field"])
+        .size_limit(SizeLimit::new().per_field(4));
+
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", base.clone());
+    assert_eq!(
+        m.next_field().await.unwrap().unwrap().read_to_end().await.unwrap(),
+        "abcd"
+    );
+
+    let derived = base.clone().size_limit(SizeLimit::new().per_field(3));
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", derived);
+    assert!(m.next_field().await.unwrap().unwrap().read_to_end().await.is_err());
+
+    let mut m = Multipart::with_constraints(str_stream(data), "X-BOUNDARY", base);
+    assert!(m.next_field().await.unwrap().unwrap().read_to_end().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_field_content_disposition() {
+    let data = "--X-BOUNDARY\r\nContent-Disposition: attachment; filename=\"a.txt\"; modification-date=\"Wed, 12 Feb 1997 16:29:51 -0500\"\r\n\r\nabcd\r\n--X-BOUNDARY--\r\n";
+    let mut m = Multipart::new(str_stream(data), "X-BOUNDARY");
+
+    let field = m.next_field().await.unwrap().unwrap();
+    let cd = field.content_disposition();
+    assert_eq!(cd.disposition_type(), Some("attachment"));
+    assert_eq!(cd.file_name(), Some("a.txt"));
+    assert_eq!(
+        cd.parse_param("modification-date").as_deref(),
+        Some("Wed, 12 Feb 1997 16:29:51 -0500")
+    );
+}